@@ -0,0 +1,55 @@
+//! Systems that use `?` on client calls and leave the error handling to a piped handler.
+//!
+//! Run next to a `Stylus.toml` with `cargo run --example fallible_systems`, then press
+//! 1, 2 or 3 to increment a color and C to log the counts.
+
+use bevy::prelude::*;
+use bevy_stylus_plugin::{StylusClient, StylusError, StylusPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(StylusPlugin::default())
+        .add_systems(
+            Update,
+            (
+                // Logs any error with `error!`, including its cause chain
+                log_counts_on_key_press.map(bevy::utils::error),
+                forge_on_key_press.pipe(handle_stylus_error),
+            )
+                .run_if(resource_exists::<StylusClient>),
+        )
+        .run();
+}
+
+fn log_counts_on_key_press(keys: Res<ButtonInput<KeyCode>>, client: Res<StylusClient>) -> eyre::Result<()> {
+    if keys.just_pressed(KeyCode::KeyC) {
+        let (red, green, blue) = client.get_sword_counts()?;
+        info!("Swords: {} / {} / {}", red, green, blue);
+    }
+    Ok(())
+}
+
+fn forge_on_key_press(keys: Res<ButtonInput<KeyCode>>, client: Res<StylusClient>) -> eyre::Result<()> {
+    for (key, color) in [(KeyCode::Digit1, 0), (KeyCode::Digit2, 1), (KeyCode::Digit3, 2)] {
+        if keys.just_pressed(key) {
+            client.increment_sword(color)?;
+            info!("Submitted sword {} increment", color);
+        }
+    }
+    Ok(())
+}
+
+/// React to specific failures, and log the rest
+fn handle_stylus_error(In(result): In<eyre::Result<()>>) {
+    let Err(e) = result else {
+        return;
+    };
+    match e.downcast_ref::<StylusError>() {
+        Some(StylusError::InsufficientGasFunds { needed, have }) => {
+            warn!("Not enough ETH for gas: need {} wei, have {} wei", needed, have);
+        }
+        Some(StylusError::ContractRevert { .. }) => warn!("The contract refused: {}", e),
+        _ => error!("Stylus call failed: {:?}", e),
+    }
+}
@@ -0,0 +1,58 @@
+//! Wires `StylusPlugin` into an app, logs the sword counts and forges swords on key presses.
+//!
+//! Run next to a `Stylus.toml` with `cargo run --example swords`, then press
+//! 1, 2 or 3 to increment a color and R to refresh the counts.
+
+use bevy::prelude::*;
+use bevy_stylus_plugin::{RefreshReads, StylusClient, StylusPlugin, SwordCounts, TxConfirmed, TxFailed};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(StylusPlugin::default())
+        .add_systems(Update, (forge_on_key_press, log_sword_counts, log_transactions))
+        .run();
+}
+
+fn forge_on_key_press(
+    keys: Res<ButtonInput<KeyCode>>,
+    client: Option<Res<StylusClient>>,
+    mut refresh: EventWriter<RefreshReads>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+
+    for (key, color) in [(KeyCode::Digit1, 0), (KeyCode::Digit2, 1), (KeyCode::Digit3, 2)] {
+        if keys.just_pressed(key) {
+            match client.increment_sword(color) {
+                Ok(()) => info!("Submitted sword {} increment", color),
+                Err(e) => error!("Failed to increment sword {}: {}", color, e),
+            }
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyR) {
+        refresh.send(RefreshReads);
+    }
+}
+
+fn log_sword_counts(counts: Option<Res<SwordCounts>>) {
+    if let Some(counts) = counts.filter(|counts| counts.is_changed()) {
+        let SwordCounts(red, green, blue) = *counts;
+        info!("Swords: {} / {} / {}", red, green, blue);
+    }
+}
+
+fn log_transactions(
+    mut confirmed: EventReader<TxConfirmed>,
+    mut failed: EventReader<TxFailed>,
+    mut refresh: EventWriter<RefreshReads>,
+) {
+    for tx in confirmed.read() {
+        info!("Transaction {:?} confirmed", tx.hash);
+        refresh.send(RefreshReads);
+    }
+    for tx in failed.read() {
+        error!("Transaction {:?} failed: {}", tx.hash, tx.reason);
+    }
+}
@@ -0,0 +1,72 @@
+use ethers::abi::{Function, HumanReadableParser, ParamType, Token};
+use ethers::prelude::{Middleware, Provider};
+use ethers::providers::{MiddlewareError, ProviderError};
+use ethers::types::{Address, TransactionRequest, H160, U256};
+use std::collections::HashSet;
+
+use crate::{StylusHttp, BLOCKCHAINCONTRACT_ABI};
+
+/// Placeholder argument of type `kind`, so probe calldata decodes cleanly
+fn zero_token(kind: &ParamType) -> Token {
+    match kind {
+        ParamType::Address => Token::Address(H160::zero()),
+        ParamType::Bytes => Token::Bytes(Vec::new()),
+        ParamType::Int(_) => Token::Int(U256::zero()),
+        ParamType::Uint(_) => Token::Uint(U256::zero()),
+        ParamType::Bool => Token::Bool(false),
+        ParamType::String => Token::String(String::new()),
+        ParamType::Array(_) => Token::Array(Vec::new()),
+        ParamType::FixedBytes(size) => Token::FixedBytes(vec![0; *size]),
+        ParamType::FixedArray(kind, size) => Token::FixedArray(vec![zero_token(kind); *size]),
+        ParamType::Tuple(kinds) => Token::Tuple(kinds.iter().map(zero_token).collect()),
+    }
+}
+
+/// The plugin's ABI functions plus the `[functions]` signatures, one per selector.
+/// Signatures that don't parse are skipped with a warning.
+fn probed_functions(signatures: &[String]) -> Vec<Function> {
+    let mut functions: Vec<Function> = BLOCKCHAINCONTRACT_ABI.functions().cloned().collect();
+    for signature in signatures {
+        match HumanReadableParser::parse_function(signature) {
+            Ok(function) => functions.push(function),
+            Err(e) => println!("⚠️ Not probing unparseable signature {}: {}", signature, e),
+        }
+    }
+    let mut seen = HashSet::new();
+    functions.retain(|function| seen.insert(function.short_signature()));
+    functions
+}
+
+/// Whether `error` is a revert without data, which is how contracts without a
+/// fallback answer an unknown selector
+fn is_empty_revert(error: &ProviderError) -> bool {
+    match error.as_error_response() {
+        Some(response) => match response.as_revert_data() {
+            Some(data) => data.is_empty(),
+            None => response.message.contains("execution reverted"),
+        },
+        None => false,
+    }
+}
+
+/// Call each function with placeholder arguments and return the signatures of those
+/// that reverted without data, i.e. that seem to be missing from the contract.
+///
+/// Functions that return or revert with data exist. A function that reverts with a
+/// bare `revert()` for zero arguments is reported too, so results are only a hint.
+pub(crate) async fn missing_functions(provider: &Provider<StylusHttp>, address: Address, signatures: &[String]) -> Vec<String> {
+    let mut missing = Vec::new();
+    for function in probed_functions(signatures) {
+        let args: Vec<Token> = function.inputs.iter().map(|input| zero_token(&input.kind)).collect();
+        let Ok(data) = function.encode_input(&args) else {
+            continue;
+        };
+        let tx = TransactionRequest::new().to(address).data(data).into();
+        match provider.call(&tx, None).await {
+            Ok(_) => {}
+            Err(e) if is_empty_revert(&e) => missing.push(function.signature()),
+            Err(e) => println!("⚠️ Failed to probe {}: {}", function.signature(), e),
+        }
+    }
+    missing
+}
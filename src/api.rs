@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use ethers::abi::Token;
+use eyre::Result;
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::StylusClient;
+
+/// The client operations game systems depend on, so they can run against a fake.
+///
+/// The plugin inserts a [`StylusApiResource`] wrapping the real client next to the
+/// concrete `StylusClient` resource; tests can insert one wrapping [`MockStylusApi`].
+pub trait StylusApi: Send + Sync + 'static {
+    fn get_sword_counts(&self) -> Result<(u64, u64, u64)>;
+    fn increment_sword(&self, color: u8) -> Result<()>;
+    fn call_view(&self, function: &str, args: &[Token]) -> Result<Vec<Token>>;
+    fn is_read_only(&self) -> bool;
+}
+
+/// Type-erased client handle, use `Res<StylusApiResource>` in systems that should be testable
+#[derive(Resource, Deref)]
+pub struct StylusApiResource(pub Box<dyn StylusApi>);
+
+impl StylusApi for StylusClient {
+    fn get_sword_counts(&self) -> Result<(u64, u64, u64)> {
+        StylusClient::get_sword_counts(self)
+    }
+
+    fn increment_sword(&self, color: u8) -> Result<()> {
+        StylusClient::increment_sword(self, color)
+    }
+
+    fn call_view(&self, function: &str, args: &[Token]) -> Result<Vec<Token>> {
+        StylusClient::call_view(self, function, args)
+    }
+
+    fn is_read_only(&self) -> bool {
+        StylusClient::is_read_only(self)
+    }
+}
+
+/// In-memory [`StylusApi`] for testing game systems without a node
+#[derive(Debug, Default)]
+pub struct MockStylusApi {
+    pub sword_counts: Mutex<(u64, u64, u64)>,
+    /// Canned `call_view` results keyed by function name
+    pub view_responses: Mutex<HashMap<String, Vec<Token>>>,
+    pub read_only: bool,
+}
+
+impl StylusApi for MockStylusApi {
+    fn get_sword_counts(&self) -> Result<(u64, u64, u64)> {
+        Ok(*self.sword_counts.lock().map_err(|_| eyre::eyre!("Mock state poisoned"))?)
+    }
+
+    fn increment_sword(&self, color: u8) -> Result<()> {
+        if self.read_only {
+            return Err(eyre::eyre!("Client is read-only"));
+        }
+        let mut counts = self.sword_counts.lock().map_err(|_| eyre::eyre!("Mock state poisoned"))?;
+        match color {
+            0 => counts.0 += 1,
+            1 => counts.1 += 1,
+            2 => counts.2 += 1,
+            _ => return Err(eyre::eyre!("Invalid sword color {}", color)),
+        }
+        Ok(())
+    }
+
+    fn call_view(&self, function: &str, _args: &[Token]) -> Result<Vec<Token>> {
+        self.view_responses
+            .lock()
+            .map_err(|_| eyre::eyre!("Mock state poisoned"))?
+            .get(function)
+            .cloned()
+            .ok_or_else(|| eyre::eyre!("No mock response for {}", function))
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
@@ -0,0 +1,208 @@
+use bevy::prelude::*;
+use ethers::prelude::{Middleware, Signer};
+use ethers::types::{Address, BlockId, U256};
+use ethers::utils::format_ether;
+use eyre::Result;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{StylusClient, StylusError};
+
+/// The signer's balance in wei, kept up to date while `balance_poll_interval_ms` is set
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignerBalance(pub U256);
+
+/// Emitted when the signer's balance differs from the previous poll
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BalanceChanged {
+    pub old: U256,
+    pub new: U256,
+}
+
+type BalanceSlot = Arc<Mutex<Option<Result<U256, String>>>>;
+
+#[derive(Resource, Default)]
+pub(crate) struct BalancePoller {
+    /// `None` until the first poll starts
+    since_last_poll: Option<Duration>,
+    last: Option<U256>,
+    in_flight: Option<BalanceSlot>,
+}
+
+impl BalancePoller {
+    /// Advance the clock by `delta` and say whether a poll should start now.
+    /// Failed polls wait a whole interval like successful ones before the next try.
+    fn tick(&mut self, delta: Duration, interval: Duration) -> bool {
+        // Fetch right away the first time so the HUD isn't empty for a whole interval
+        self.since_last_poll = self.since_last_poll.map(|since| since + delta);
+        let due = self.since_last_poll.is_none_or(|since| since >= interval);
+        if !due || self.in_flight.is_some() {
+            return false;
+        }
+        self.since_last_poll = Some(Duration::ZERO);
+        true
+    }
+}
+
+impl StylusClient {
+    /// ETH balance of any account or contract, in wei
+    pub fn get_balance(&self, address: Address) -> Result<U256> {
+        self.get_balance_at_block(address, None)
+    }
+
+    /// ETH balance of any account or contract in wei, optionally at a historical block
+    pub fn get_balance_at_block(&self, address: Address, block: Option<BlockId>) -> Result<U256> {
+        let provider = self.provider.as_ref().ok_or(StylusError::NotInitialized)?;
+        self.block_on_rpc("eth_getBalance", provider.get_balance(address, block))
+    }
+
+    /// ETH held by the game contract itself (e.g. a prize pool), in wei.
+    /// Unlike [`signer_balance`](Self::signer_balance) this is the contract's own balance.
+    pub fn contract_balance(&self) -> Result<U256> {
+        self.contract_balance_at_block(None)
+    }
+
+    /// ETH held by the game contract in wei, optionally at a historical block
+    pub fn contract_balance_at_block(&self, block: Option<BlockId>) -> Result<U256> {
+        let address = self.contract_address.ok_or(StylusError::NotInitialized)?;
+        self.get_balance_at_block(address, block)
+    }
+
+    /// The contract's ETH balance formatted in ether for display, e.g. `"1.250000000000000000"`
+    pub fn contract_balance_ether(&self) -> Result<String> {
+        Ok(format_ether(self.contract_balance()?))
+    }
+
+    /// Current balance of the signer's wallet, in wei
+    pub fn signer_balance(&self) -> Result<U256> {
+        let signer = self
+            .contract_client
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Signer not initialized"))?;
+        self.get_balance(signer.signer().address())
+    }
+}
+
+/// Poll the signer's balance on `balance_poll_interval` and emit `BalanceChanged`.
+///
+/// Only the value seen at each poll is compared, so several transactions landing
+/// between two polls produce a single event.
+pub(crate) fn poll_signer_balance(
+    mut commands: Commands,
+    client: Option<Res<StylusClient>>,
+    time: Res<Time>,
+    mut poller: ResMut<BalancePoller>,
+    mut changes: EventWriter<BalanceChanged>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+    let Some(interval) = client.balance_poll_interval else {
+        return;
+    };
+    if client.contract_client.is_none() {
+        return;
+    }
+
+    let finished = poller
+        .in_flight
+        .as_ref()
+        .and_then(|slot| slot.lock().ok().and_then(|mut result| result.take()));
+    if let Some(result) = finished {
+        poller.in_flight = None;
+        match result {
+            Ok(new) => {
+                if let Some(old) = poller.last.filter(|old| *old != new) {
+                    changes.send(BalanceChanged { old, new });
+                }
+                if poller.last != Some(new) {
+                    commands.insert_resource(SignerBalance(new));
+                    poller.last = Some(new);
+                }
+            }
+            Err(e) => println!("⚠️ Failed to poll signer balance: {}", e),
+        }
+    }
+
+    if !poller.tick(time.delta(), interval) {
+        return;
+    }
+
+    let slot: BalanceSlot = Arc::default();
+    poller.in_flight = Some(slot.clone());
+    let client = client.clone();
+    std::thread::spawn(move || {
+        let result = client.signer_balance().map_err(|e| e.to_string());
+        if let Ok(mut slot) = slot.lock() {
+            *slot = Some(result);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{contract_address, MockNode};
+    use ethers::types::BlockNumber;
+    use serde_json::Value;
+
+    const INTERVAL: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn first_poll_starts_immediately() {
+        let mut poller = BalancePoller::default();
+        assert!(poller.tick(Duration::ZERO, INTERVAL));
+    }
+
+    #[test]
+    fn failed_poll_waits_for_the_interval() {
+        let mut poller = BalancePoller::default();
+        assert!(poller.tick(Duration::ZERO, INTERVAL));
+        // The poll failed, so `last` is still unset
+        assert!(poller.last.is_none());
+        assert!(!poller.tick(Duration::from_secs(1), INTERVAL));
+        assert!(!poller.tick(Duration::from_secs(3), INTERVAL));
+        assert!(poller.tick(Duration::from_secs(1), INTERVAL));
+    }
+
+    #[test]
+    fn no_second_poll_while_one_is_in_flight() {
+        let mut poller = BalancePoller::default();
+        assert!(poller.tick(Duration::ZERO, INTERVAL));
+        poller.in_flight = Some(Arc::default());
+        assert!(!poller.tick(Duration::from_secs(10), INTERVAL));
+        poller.in_flight = None;
+        assert!(poller.tick(Duration::ZERO, INTERVAL));
+    }
+
+    /// A node where the contract holds 1 ether at `latest` and 2 wei at block 0x10
+    fn prize_pool_node() -> MockNode {
+        MockNode::start(|method, params| {
+            assert_eq!(method, "eth_getBalance");
+            assert_eq!(params[0].as_str(), Some(format!("{:?}", contract_address()).as_str()));
+            Ok(Value::String(match params[1].as_str() {
+                Some("latest") => "0xde0b6b3a7640000",
+                Some("0x10") => "0x2",
+                other => panic!("unexpected block {:?}", other),
+            }
+            .to_string()))
+        })
+    }
+
+    #[test]
+    fn contract_balance_reads_the_contract_account() {
+        let client = prize_pool_node().client();
+        assert_eq!(client.contract_balance().unwrap(), U256::exp10(18));
+        assert_eq!(client.contract_balance_ether().unwrap(), "1.000000000000000000");
+        let block = Some(BlockId::Number(BlockNumber::Number(16.into())));
+        assert_eq!(client.contract_balance_at_block(block).unwrap(), U256::from(2));
+    }
+
+    #[test]
+    fn contract_balance_needs_a_contract() {
+        let error = StylusClient::default().contract_balance().unwrap_err();
+        assert!(matches!(error.downcast_ref::<StylusError>(), Some(StylusError::NotInitialized)));
+    }
+}
@@ -0,0 +1,200 @@
+//! Browser wallet support for wasm builds.
+//!
+//! Requests are routed through the injected EIP-1193 provider (`window.ethereum`,
+//! e.g. MetaMask), so transactions are signed by the player's wallet instead of a
+//! raw private key. The account and chain id are taken from the wallet.
+
+use crate::{BlockchainContract, StylusClient};
+use async_trait::async_trait;
+use ethers::prelude::{Middleware, Provider};
+use ethers::providers::{JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use ethers::types::{Address, TxHash, U256};
+use eyre::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt, sync::Arc};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// JSON-RPC transport backed by the injected EIP-1193 provider
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Eip1193;
+
+/// Errors returned by the [`Eip1193`] transport
+#[derive(Debug)]
+pub enum Eip1193Error {
+    /// No injected provider was found on `window`
+    NotAvailable,
+    /// The wallet rejected or failed the request
+    JsonRpc(JsonRpcError),
+    /// The request or response could not be (de)serialized
+    Serde(serde_json::Error),
+}
+
+impl fmt::Display for Eip1193Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Eip1193Error::NotAvailable => write!(f, "No injected browser wallet found (window.ethereum)"),
+            Eip1193Error::JsonRpc(e) => write!(f, "Browser wallet error: {}", e),
+            Eip1193Error::Serde(e) => write!(f, "Browser wallet (de)serialization error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Eip1193Error {}
+
+impl RpcError for Eip1193Error {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            Eip1193Error::JsonRpc(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            Eip1193Error::Serde(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<Eip1193Error> for ProviderError {
+    fn from(src: Eip1193Error) -> Self {
+        match src {
+            Eip1193Error::Serde(e) => ProviderError::SerdeJson(e),
+            e => ProviderError::JsonRpcClientError(Box::new(e)),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Eip1193Error {
+    fn from(src: serde_json::Error) -> Self {
+        Eip1193Error::Serde(src)
+    }
+}
+
+impl Eip1193 {
+    /// Whether an injected provider is present on the page
+    pub fn is_available() -> bool {
+        injected_provider().is_ok()
+    }
+}
+
+fn injected_provider() -> Result<JsValue, Eip1193Error> {
+    let window = web_sys::window().ok_or(Eip1193Error::NotAvailable)?;
+    let ethereum = js_sys::Reflect::get(&window, &JsValue::from_str("ethereum"))
+        .map_err(|_| Eip1193Error::NotAvailable)?;
+    if ethereum.is_undefined() || ethereum.is_null() {
+        Err(Eip1193Error::NotAvailable)
+    } else {
+        Ok(ethereum)
+    }
+}
+
+/// Convert a rejected wallet promise (`{ code, message, data }`) into a JSON-RPC error
+fn js_error(value: JsValue) -> Eip1193Error {
+    let field = |name: &str| js_sys::Reflect::get(&value, &JsValue::from_str(name)).ok();
+    let code = field("code").and_then(|c| c.as_f64()).unwrap_or(-32603.0) as i64;
+    let message = field("message")
+        .and_then(|m| m.as_string())
+        .unwrap_or_else(|| format!("{:?}", value));
+    let data = field("data")
+        .filter(|d| !d.is_undefined())
+        .and_then(|d| js_sys::JSON::stringify(&d).ok())
+        .and_then(|d| serde_json::from_str(&String::from(d)).ok());
+    Eip1193Error::JsonRpc(JsonRpcError { code, message, data })
+}
+
+#[async_trait(?Send)]
+impl JsonRpcClient for Eip1193 {
+    type Error = Eip1193Error;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Eip1193Error>
+    where
+        T: fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let ethereum = injected_provider()?;
+
+        let request = js_sys::Object::new();
+        js_sys::Reflect::set(&request, &JsValue::from_str("method"), &JsValue::from_str(method))
+            .map_err(js_error)?;
+        let params = js_sys::JSON::parse(&serde_json::to_string(&params)?).map_err(js_error)?;
+        // ethers encodes "no params" as null, EIP-1193 expects the field to be omitted
+        if !params.is_null() {
+            js_sys::Reflect::set(&request, &JsValue::from_str("params"), &params).map_err(js_error)?;
+        }
+
+        let request_fn: js_sys::Function = js_sys::Reflect::get(&ethereum, &JsValue::from_str("request"))
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(|_| Eip1193Error::NotAvailable)?;
+        let promise: js_sys::Promise = request_fn
+            .call1(&ethereum, &request)
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(js_error)?;
+
+        let value = JsFuture::from(promise).await.map_err(js_error)?;
+        let json = if value.is_undefined() {
+            "null".to_string()
+        } else {
+            js_sys::JSON::stringify(&value).map_err(js_error)?.into()
+        };
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// A browser wallet connected through the injected EIP-1193 provider
+#[derive(Debug, Clone)]
+pub struct BrowserWallet {
+    pub provider: Arc<Provider<Eip1193>>,
+    pub account: Address,
+    pub chain_id: u64,
+}
+
+impl BrowserWallet {
+    /// Request account access from the injected wallet (prompts the player)
+    pub async fn connect() -> Result<Self> {
+        let provider = Provider::new(Eip1193);
+        let accounts: Vec<Address> = provider.request("eth_requestAccounts", ()).await?;
+        let account = accounts
+            .first()
+            .copied()
+            .ok_or_else(|| eyre::eyre!("Browser wallet returned no accounts"))?;
+        let chain_id = provider.get_chainid().await?.as_u64();
+
+        Ok(Self {
+            provider: Arc::new(provider.with_sender(account)),
+            account,
+            chain_id,
+        })
+    }
+}
+
+impl StylusClient {
+    /// Route writes through a connected browser wallet instead of a local signer
+    pub fn attach_browser_wallet(&mut self, wallet: BrowserWallet) -> Result<()> {
+        let contract_address = self
+            .contract_address
+            .ok_or_else(|| eyre::eyre!("Contract not initialized"))?;
+
+        println!("🦊 Using browser wallet {:?} on chain {}", wallet.account, wallet.chain_id);
+
+        self.browser_contract = Some(BlockchainContract::new(contract_address, wallet.provider.clone()));
+        self.browser_wallet = Some(wallet);
+        self.read_only_reason = None;
+        Ok(())
+    }
+
+    /// Increment sword count through the browser wallet, which prompts the player to sign
+    pub async fn increment_sword_browser(&self, color: u8) -> Result<TxHash> {
+        if let Some(contract) = &self.browser_contract {
+            let call = contract.increment_sword(U256::from(color));
+            let pending = call.send().await?;
+            Ok(pending.tx_hash())
+        } else {
+            Err(eyre::eyre!("Browser wallet not connected"))
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use ethers::prelude::Middleware;
+use eyre::Result;
+use std::sync::{Arc, Mutex};
+
+use crate::{StylusClient, StylusError};
+
+/// A value together with the chain head it was read at
+#[derive(Debug, Clone)]
+pub(crate) struct BlockCache<T>(Arc<Mutex<Option<(u64, T)>>>);
+
+impl<T> Default for BlockCache<T> {
+    fn default() -> Self {
+        BlockCache(Arc::default())
+    }
+}
+
+impl<T: Clone> BlockCache<T> {
+    /// The cached value if it was read at most `max_block_lag` blocks before `head`
+    fn get(&self, head: u64, max_block_lag: u64) -> Option<T> {
+        let cached = self.0.lock().ok()?;
+        let (fetched_at, value) = cached.as_ref()?;
+        (head.saturating_sub(*fetched_at) <= max_block_lag).then(|| value.clone())
+    }
+
+    fn store(&self, head: u64, value: T) {
+        if let Ok(mut cached) = self.0.lock() {
+            *cached = Some((head, value));
+        }
+    }
+}
+
+impl StylusClient {
+    /// [`get_sword_counts`](Self::get_sword_counts), reusing the last result while the
+    /// chain head is at most `max_block_lag` blocks past the block it was read at.
+    ///
+    /// Staleness is measured in blocks rather than time, which stays meaningful when
+    /// block times vary. Each call still asks for the block number, but skips the view
+    /// call while the cached value is fresh enough. `0` only reuses reads from the current head.
+    pub fn get_sword_counts_fresh(&self, max_block_lag: u64) -> Result<(u64, u64, u64)> {
+        let provider = self.provider.as_ref().ok_or(StylusError::NotInitialized)?;
+        let head = self.block_on_rpc("eth_blockNumber", provider.get_block_number())?.as_u64();
+        if let Some(counts) = self.sword_counts_cache.get(head, max_block_lag) {
+            return Ok(counts);
+        }
+        let counts = self.get_sword_counts()?;
+        self.sword_counts_cache.store(head, counts);
+        Ok(counts)
+    }
+}
@@ -0,0 +1,259 @@
+use ethers::abi::{Abi, Function, Token};
+use ethers::prelude::Middleware;
+use ethers::providers::{JsonRpcError, MiddlewareError};
+use ethers::types::{Bytes, TransactionRequest, I256};
+use ethers::utils::to_checksum;
+use eyre::Result;
+use serde_json::{Map, Value};
+use std::time::{Duration, Instant};
+
+use crate::{format_tokens, StylusClient, StylusError, BLOCKCHAINCONTRACT_ABI};
+
+/// Per-call limits for [`StylusClient::call_view_with`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CallOptions {
+    /// Give up once the call has been running this long, failing with `StylusError::Timeout`
+    pub timeout: Option<Duration>,
+    /// Give up at this wall-clock instant (e.g. when a turn expires), failing with
+    /// `StylusError::Deadline`. Retries share the deadline rather than restarting it.
+    pub deadline: Option<Instant>,
+}
+
+impl CallOptions {
+    /// The instant a call started at `started` must finish by, and the error raised once it hasn't
+    fn cutoff(&self, method: &str, started: Instant) -> Option<(Instant, StylusError)> {
+        let timeout = self.timeout.map(|timeout| {
+            (started + timeout, StylusError::Timeout { method: method.to_string(), timeout })
+        });
+        let deadline = self
+            .deadline
+            .map(|deadline| (deadline, StylusError::Deadline { method: method.to_string() }));
+        match (timeout, deadline) {
+            (Some(timeout), Some(deadline)) => Some(if deadline.0 <= timeout.0 { deadline } else { timeout }),
+            (timeout, deadline) => timeout.or(deadline),
+        }
+    }
+}
+
+impl StylusClient {
+    /// Like `run_rpc`, but aborts once the call started at `started` runs out of time
+    pub(crate) fn run_rpc_within<F: std::future::Future>(
+        &self,
+        method: &str,
+        options: &CallOptions,
+        started: Instant,
+        future: F,
+    ) -> Result<F::Output> {
+        let Some((cutoff, error)) = options.cutoff(method, started) else {
+            return self.run_rpc(method, future);
+        };
+        let remaining = cutoff
+            .checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+            .ok_or_else(|| self.log_error(method, error.clone().into()))?;
+        self.run_rpc(method, async move { tokio::time::timeout(remaining, future).await })?
+            .map_err(|_| self.log_error(method, error.into()))
+    }
+
+    /// ABI used for generic calls and for decoding revert data
+    pub fn abi(&self) -> &Abi {
+        &BLOCKCHAINCONTRACT_ABI
+    }
+
+    /// Function signatures from the `[functions]` section of `Stylus.toml`
+    pub fn function_signatures(&self) -> &[String] {
+        &self.function_signatures
+    }
+
+    /// Whether a configured signature declares a function called `name`
+    pub fn has_function(&self, name: &str) -> bool {
+        self.function_signatures.iter().any(|signature| signature_name(signature) == name)
+    }
+
+    fn function(&self, name: &str) -> Result<&Function> {
+        self.abi()
+            .function(name)
+            .map_err(|_| eyre::eyre!("Function {} not found in contract ABI", name))
+    }
+
+    /// Decode calldata, e.g. a pending transaction's input, into its function name and arguments.
+    /// Fails when the selector matches no function in the ABI.
+    pub fn decode_calldata(&self, data: &Bytes) -> Result<(String, Vec<Token>)> {
+        let (selector, args) = data
+            .split_first_chunk::<4>()
+            .ok_or_else(|| eyre::eyre!("Calldata too short for a selector: {}", data))?;
+        let function = self
+            .abi()
+            .functions()
+            .find(|function| function.short_signature() == *selector)
+            .ok_or_else(|| eyre::eyre!("Unknown selector {}", Bytes::from(selector.to_vec())))?;
+        Ok((function.name.clone(), function.decode_input(args)?))
+    }
+
+    /// Call a view function by name and return its raw output tokens
+    pub fn call_view(&self, function: &str, args: &[Token]) -> Result<Vec<Token>> {
+        self.call_view_with(function, args, &CallOptions::default())
+    }
+
+    /// [`call_view`](Self::call_view) bounded by a timeout and/or deadline.
+    ///
+    /// The block-tag fallback retry runs within the same budget as the first attempt.
+    pub fn call_view_with(&self, function: &str, args: &[Token], options: &CallOptions) -> Result<Vec<Token>> {
+        let started = Instant::now();
+        let abi_function = self.function(function)?;
+        if let (Some(provider), Some(address)) = (&self.provider, self.contract_address) {
+            let data = abi_function.encode_input(args)?;
+            let tx = TransactionRequest::new().to(address).data(data).into();
+            let output = self.with_read_block(|block| {
+                self.run_rpc_within(function, options, started, provider.call(&tx, block))?
+                    .map_err(|e| self.decode_provider_error(function, e))
+            })?;
+            if output.len() < 32 * abi_function.outputs.len() {
+                let error = StylusError::EmptyResponse { function: function.to_string() };
+                return Err(self.log_error(function, error.into()));
+            }
+            Ok(abi_function.decode_output(&output)?)
+        } else {
+            Err(eyre::eyre!("Contract not initialized"))
+        }
+    }
+
+    /// Call a view function that returns a single `bool`, e.g. `isAllowed(address)`
+    pub fn call_view_bool(&self, function: &str, args: &[Token]) -> Result<bool> {
+        match self.call_view(function, args)?.as_slice() {
+            [Token::Bool(value)] => Ok(*value),
+            tokens => Err(eyre::eyre!("{} returned ({}), expected a single bool", function, format_tokens(tokens))),
+        }
+    }
+
+    /// Call a view function by name and return its output as JSON.
+    ///
+    /// The result is an object keyed by the ABI output names, or by position
+    /// (`"0"`, `"1"`, ...) for unnamed outputs. See [`token_to_json`] for how
+    /// individual values are represented.
+    pub fn call_view_json(&self, function: &str, args: &[Token]) -> Result<Value> {
+        let abi_function = self.function(function)?;
+        let tokens = self.call_view(function, args)?;
+        let fields = abi_function
+            .outputs
+            .iter()
+            .zip(tokens.iter())
+            .enumerate()
+            .map(|(index, (output, token))| {
+                let key = if output.name.is_empty() { index.to_string() } else { output.name.clone() };
+                (key, token_to_json(token))
+            })
+            .collect::<Map<_, _>>();
+        Ok(Value::Object(fields))
+    }
+
+    /// Turn a failed RPC call into `StylusError::ContractRevert` when it carries revert data
+    pub(crate) fn decode_provider_error<E: MiddlewareError + 'static>(&self, method: &str, error: E) -> eyre::Report {
+        let error = match error.as_error_response().and_then(JsonRpcError::as_revert_data) {
+            Some(data) => StylusError::from_revert_data(self.abi(), data).into(),
+            None => error.into(),
+        };
+        self.log_error(method, error)
+    }
+}
+
+/// Function name of a signature such as `function incrementSword(uint256 color) external`
+fn signature_name(signature: &str) -> &str {
+    let signature = signature.trim();
+    let signature = signature.strip_prefix("function ").unwrap_or(signature);
+    signature.split('(').next().unwrap_or_default().trim()
+}
+
+/// Convert an ABI token to JSON.
+///
+/// Integers become JSON numbers when they fit in 64 bits and decimal strings
+/// otherwise; addresses are checksummed hex, bytes are `0x`-prefixed hex, and
+/// tuples and arrays become JSON arrays.
+pub fn token_to_json(token: &Token) -> Value {
+    match token {
+        Token::Address(address) => Value::String(to_checksum(address, None)),
+        Token::FixedBytes(bytes) | Token::Bytes(bytes) => Value::String(Bytes::from(bytes.clone()).to_string()),
+        Token::Int(value) => {
+            let value = I256::from_raw(*value);
+            match i64::try_from(value) {
+                Ok(value) => Value::from(value),
+                Err(_) => Value::String(value.to_string()),
+            }
+        }
+        Token::Uint(value) => match u64::try_from(*value) {
+            Ok(value) => Value::from(value),
+            Err(_) => Value::String(value.to_string()),
+        },
+        Token::Bool(value) => Value::Bool(*value),
+        Token::String(value) => Value::String(value.clone()),
+        Token::FixedArray(tokens) | Token::Array(tokens) | Token::Tuple(tokens) => {
+            Value::Array(tokens.iter().map(token_to_json).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{call_data, encoded, MockNode};
+
+    /// A node whose contract answers every call with `output`
+    fn node_returning(output: serde_json::Value) -> MockNode {
+        MockNode::start(move |method, _| {
+            assert_eq!(method, "eth_call");
+            Ok(output.clone())
+        })
+    }
+
+    #[test]
+    fn empty_view_output_is_reported_as_empty_response() {
+        let client = node_returning(serde_json::Value::String("0x".to_string())).client();
+        let error = client.call_view("getSwordCounts", &[]).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<StylusError>(),
+            Some(StylusError::EmptyResponse { function }) if function == "getSwordCounts"
+        ));
+    }
+
+    #[test]
+    fn empty_typed_output_is_reported_as_empty_response() {
+        let client = node_returning(serde_json::Value::String("0x".to_string())).client();
+        let error = client.get_sword_counts().unwrap_err();
+        assert!(matches!(error.downcast_ref::<StylusError>(), Some(StylusError::EmptyResponse { .. })), "{:?}", error);
+    }
+
+    #[test]
+    fn full_output_decodes() {
+        let counts = [Token::Uint(1.into()), Token::Uint(2.into()), Token::Uint(3.into())];
+        let client = node_returning(encoded(&counts)).client();
+        assert_eq!(client.call_view("getSwordCounts", &[]).unwrap(), counts);
+        assert_eq!(client.get_sword_counts().unwrap(), (1, 2, 3));
+    }
+
+    #[test]
+    fn call_view_bool_returns_a_single_bool() {
+        let args = [Token::Address(Default::default())];
+        for value in [true, false] {
+            let client = node_returning(encoded(&[Token::Bool(value)])).client();
+            assert_eq!(client.call_view_bool("canIncrement", &args).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn call_view_bool_rejects_other_outputs() {
+        let counts = [Token::Uint(1.into()), Token::Uint(2.into()), Token::Uint(3.into())];
+        let client = node_returning(encoded(&counts)).client();
+        let error = client.call_view_bool("getSwordCounts", &[]).unwrap_err();
+        assert_eq!(error.to_string(), "getSwordCounts returned (1, 2, 3), expected a single bool");
+    }
+
+    #[test]
+    fn call_data_targets_the_named_function() {
+        let node = MockNode::start(|_, params| {
+            let (selector, _) = call_data(params);
+            assert_eq!(selector, BLOCKCHAINCONTRACT_ABI.function("getSwordCounts").unwrap().short_signature());
+            Ok(encoded(&[Token::Uint(0.into()), Token::Uint(0.into()), Token::Uint(0.into())]))
+        });
+        assert!(node.client().call_view("getSwordCounts", &[]).is_ok());
+    }
+}
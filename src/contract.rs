@@ -0,0 +1,433 @@
+use ethers::abi::Detokenize;
+use ethers::prelude::{ContractCall, ContractError, LocalWallet, Middleware, Provider, SignerMiddleware};
+use ethers::types::{
+    transaction::eip2718::TypedTransaction, Address, Eip1559TransactionRequest, TransactionRequest, TxHash, U256,
+};
+use eyre::Result;
+use std::{ops::Deref, sync::Arc};
+
+use crate::{dedup::write_key, StylusClient, StylusError, StylusEvent, StylusHttp};
+
+/// Signing middleware shared by every contract bound through the client
+pub type StylusSigner = SignerMiddleware<Provider<StylusHttp>, LocalWallet>;
+
+/// Upper bound on `gas_price_premium_percent`, larger values are clamped
+pub(crate) const MAX_GAS_PRICE_PREMIUM_PERCENT: u32 = 100;
+
+/// Per-call overrides for the shared write path
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SendOptions {
+    /// Confirmation depth, instead of the one the confirmation policy picks
+    pub depth: Option<usize>,
+    /// Refuse to send when the estimated cost in wei is higher
+    pub max_spend: Option<U256>,
+    /// Always broadcast, even when an identical write is within the dedup window
+    pub skip_dedup: bool,
+}
+
+/// Fees for a transaction replacing a stuck one, see [`StylusClient::cancel_tx`]
+#[derive(Debug, Clone, Copy)]
+pub struct GasConfig {
+    /// Percent the stuck transaction's fees are raised by. Nodes reject replacements
+    /// that don't raise both fee fields by about 10% or more.
+    pub bump_percent: u32,
+    /// Exact max fee (or gas price, for legacy transactions) instead of the bumped one
+    pub max_fee_per_gas: Option<U256>,
+    /// Exact priority fee instead of the bumped one
+    pub max_priority_fee_per_gas: Option<U256>,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self { bump_percent: 15, max_fee_per_gas: None, max_priority_fee_per_gas: None }
+    }
+}
+
+/// A user `abigen!` contract bound to the client's middleware.
+///
+/// Calls made through it share the client's RPC watchdog, read block tag,
+/// revert decoding and pending-transaction tracking. It derefs to the wrapped
+/// contract, so its generated method builders are available directly:
+///
+/// ```no_run
+/// use bevy_stylus_plugin::StylusClient;
+/// use ethers::prelude::abigen;
+///
+/// abigen!(
+///     Counter,
+///     r#"[
+///         function count() external view returns (uint256)
+///         function increment() external
+///     ]"#
+/// );
+///
+/// fn bump(client: &StylusClient) -> eyre::Result<()> {
+///     let address = "0x0000000000000000000000000000000000000001".parse()?;
+///     let counter = client.bind_contract(address, Counter::new)?;
+///     println!("count: {}", counter.call(counter.count())?);
+///     counter.send(counter.increment())?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct StylusContract<C> {
+    client: StylusClient,
+    address: Address,
+    contract: C,
+}
+
+impl<C> StylusContract<C> {
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.contract
+    }
+
+    /// Execute a view call built from the wrapped contract
+    pub fn call<M: Middleware + 'static, D: Detokenize>(&self, call: ContractCall<M, D>) -> Result<D> {
+        self.client.call_contract(call)
+    }
+
+    /// Send a write built from the wrapped contract, tracking it until mined
+    pub fn send<M: Middleware + 'static, D: Detokenize>(&self, call: ContractCall<M, D>) -> Result<TxHash> {
+        self.client.send_contract(call)
+    }
+}
+
+impl<C> Deref for StylusContract<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.contract
+    }
+}
+
+impl StylusClient {
+    /// Bind a user contract to the client's signer, e.g. `client.bind_contract(address, MyContract::new)`
+    pub fn bind_contract<C>(
+        &self,
+        address: Address,
+        new: impl FnOnce(Address, Arc<StylusSigner>) -> C,
+    ) -> Result<StylusContract<C>> {
+        let signer = self
+            .contract_client
+            .clone()
+            .ok_or_else(|| eyre::eyre!("Signer not initialized"))?;
+        Ok(StylusContract { client: self.clone(), address, contract: new(address, signer) })
+    }
+
+    /// Bind a user contract to the plain provider, for view calls only
+    pub fn bind_read_contract<C>(
+        &self,
+        address: Address,
+        new: impl FnOnce(Address, Arc<Provider<StylusHttp>>) -> C,
+    ) -> Result<StylusContract<C>> {
+        let provider = self
+            .provider
+            .clone()
+            .ok_or_else(|| eyre::eyre!("Contract not initialized"))?;
+        Ok(StylusContract { client: self.clone(), address, contract: new(address, provider) })
+    }
+
+    /// Execute a view call from any `abigen!` contract at the configured read block tag
+    pub fn call_contract<M: Middleware + 'static, D: Detokenize>(&self, call: ContractCall<M, D>) -> Result<D> {
+        let method = call.function.name.clone();
+        self.with_read_block(|block| {
+            let mut call = call.clone();
+            if block.is_some() {
+                call.block = block;
+            }
+            match self.run_rpc(&method, call.call())? {
+                Err(ContractError::DecodingError(_) | ContractError::AbiError(_) | ContractError::DetokenizationError(_))
+                    if self.returned_short_data(&method, &call) =>
+                {
+                    Err(self.log_error(&method, StylusError::EmptyResponse { function: method.clone() }.into()))
+                }
+                result => result.map_err(|e| self.decode_contract_error(&method, e)),
+            }
+        })
+    }
+
+    /// Re-run a call whose output failed to decode and check whether the node returned
+    /// less data than the outputs need (e.g. `0x` from an unactivated Stylus program)
+    fn returned_short_data<M: Middleware + 'static, D: Detokenize>(&self, method: &str, call: &ContractCall<M, D>) -> bool {
+        matches!(
+            self.run_rpc(method, call.call_raw_bytes()),
+            Ok(Ok(bytes)) if bytes.len() < 32 * call.function.outputs.len()
+        )
+    }
+
+    /// Send a write from any `abigen!` contract, signed by the client's wallet, and track it until mined
+    pub fn send_contract<M: Middleware + 'static, D: Detokenize>(&self, call: ContractCall<M, D>) -> Result<TxHash> {
+        self.send_transaction_as(&call.function.name, call.tx)
+    }
+
+    /// Sign and broadcast a pre-built transaction, tracking it until mined.
+    ///
+    /// The sender is always the client's wallet. Any of nonce, gas limit, gas
+    /// price / EIP-1559 fees and chain id left unset on `tx` are filled from the
+    /// node before signing; values already set are sent as-is. Fails in read-only mode.
+    pub fn send_raw(&self, tx: TypedTransaction) -> Result<TxHash> {
+        self.send_transaction_as("eth_sendRawTransaction", tx)
+    }
+
+    /// [`send_raw`](Self::send_raw), waiting for `depth` blocks on top of the including
+    /// one before `TxConfirmed` instead of the depth the confirmation policy picks
+    pub fn send_raw_with_depth(&self, tx: TypedTransaction, depth: usize) -> Result<TxHash> {
+        let options = SendOptions { depth: Some(depth), ..Default::default() };
+        self.send_transaction_with("eth_sendRawTransaction", tx, options)
+    }
+
+    /// Shared write path, `function` names the transaction in metrics and pending tracking
+    pub(crate) fn send_transaction_as(&self, function: &str, tx: TypedTransaction) -> Result<TxHash> {
+        self.send_transaction_with(function, tx, SendOptions::default())
+    }
+
+    pub(crate) fn send_transaction_with(&self, function: &str, tx: TypedTransaction, options: SendOptions) -> Result<TxHash> {
+        if let Some(reason) = &self.read_only_reason {
+            return Err(eyre::eyre!("Client is read-only: {}", reason));
+        }
+        let key = write_key(function, &tx);
+        if !options.skip_dedup {
+            if let Some(tx_hash) = self.write_dedup.claim(&key) {
+                return Ok(tx_hash);
+            }
+        }
+        let result = self.submit_transaction(function, tx.clone(), options);
+        if !options.skip_dedup {
+            self.write_dedup.finish(&key, result.as_ref().ok().copied());
+        }
+        self.tx_history.record(function, tx, &result);
+        result
+    }
+
+    fn submit_transaction(&self, function: &str, tx: TypedTransaction, options: SendOptions) -> Result<TxHash> {
+        let depth = options.depth.unwrap_or_else(|| self.confirmation_policy.depth_for(tx.value().copied().unwrap_or_default()));
+        let signer = self
+            .contract_client
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Signer not initialized"))?;
+        // The relayer pays for gas, so the fee and funds checks don't apply
+        if let Some(relayer) = &self.relayer {
+            let tx_hash = self.relay_transaction(relayer, signer, &tx)?;
+            self.track_pending(tx_hash, function, depth);
+            return Ok(tx_hash);
+        }
+        let mut tx = tx;
+        if self.gas_price_premium_percent > 0 {
+            self.apply_gas_price_premium(signer, &mut tx)?;
+        }
+        if let Some(max) = options.max_spend {
+            self.fill_transaction(signer, &mut tx)?;
+            let cost = max_cost(&tx);
+            if cost > max {
+                return Err(StylusError::CostExceedsMax { cost, max }.into());
+            }
+        }
+        if self.check_gas_funds {
+            self.ensure_gas_funds(signer, &mut tx)?;
+        }
+        if let Some(url) = &self.private_tx_url {
+            match self.send_private_transaction(url, signer, tx.clone())? {
+                Some(tx_hash) => {
+                    self.track_pending(tx_hash, function, depth);
+                    return Ok(tx_hash);
+                }
+                None => println!("⚠️ Sending {} publicly instead", function),
+            }
+        }
+        let tx_hash = self
+            .run_rpc(function, async { signer.send_transaction(tx, None).await.map(|pending| pending.tx_hash()) })?
+            .map_err(|e| self.decode_provider_error(function, e))?;
+        self.track_pending(tx_hash, function, depth);
+        Ok(tx_hash)
+    }
+
+    /// Cancel a stuck transaction by sending a 0-ETH transfer to our own address with its nonce.
+    ///
+    /// The replacement only takes the nonce if its fees beat the stuck transaction's,
+    /// so both fee fields are raised by `gas.bump_percent` unless given explicitly. Once
+    /// the replacement mines, the original is reported through `TxCancelled` and its
+    /// history entry becomes `TxStatus::Cancelled`. If the original mines first, the
+    /// replacement is dropped instead.
+    pub fn cancel_tx(&self, stuck: TxHash, gas: GasConfig) -> Result<TxHash> {
+        let signer = self
+            .contract_client
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Signer not initialized"))?;
+        let original = self
+            .get_transaction(stuck)?
+            .ok_or_else(|| eyre::eyre!("Transaction {:?} not found", stuck))?;
+        if original.block_number.is_some() {
+            return Err(eyre::eyre!("Transaction {:?} is already mined", stuck));
+        }
+        let own_address = signer.address();
+        if original.from != own_address {
+            return Err(eyre::eyre!("Transaction {:?} was not sent by our wallet", stuck));
+        }
+
+        let bump = |fee: U256| fee.saturating_add(fee.saturating_mul(U256::from(gas.bump_percent)) / 100);
+        let tx: TypedTransaction = match (original.max_fee_per_gas, original.max_priority_fee_per_gas) {
+            (Some(max_fee), Some(priority_fee)) => Eip1559TransactionRequest::new()
+                .to(own_address)
+                .value(0)
+                .nonce(original.nonce)
+                .max_fee_per_gas(gas.max_fee_per_gas.unwrap_or_else(|| bump(max_fee)))
+                .max_priority_fee_per_gas(gas.max_priority_fee_per_gas.unwrap_or_else(|| bump(priority_fee)))
+                .into(),
+            _ => TransactionRequest::new()
+                .to(own_address)
+                .value(0)
+                .nonce(original.nonce)
+                .gas_price(gas.max_fee_per_gas.unwrap_or_else(|| bump(original.gas_price.unwrap_or_default())))
+                .into(),
+        };
+
+        // Sent directly: a relayer would use its own nonce and replace nothing
+        let result = self
+            .run_rpc("cancel_tx", async { signer.send_transaction(tx.clone(), None).await.map(|pending| pending.tx_hash()) })?
+            .map_err(|e| self.decode_provider_error("cancel_tx", e));
+        self.tx_history.record("cancel_tx", tx, &result);
+        let tx_hash = result?;
+        println!("🛑 Sent {:?} to cancel stuck transaction {:?}", tx_hash, stuck);
+        self.pending_transactions.track_replacement(tx_hash, "cancel_tx", stuck);
+        self.event_stream.publish(StylusEvent::Submitted { hash: tx_hash, function: "cancel_tx".to_string() });
+        if let Some(provider) = &self.provider {
+            self.pending_transactions.watch(tx_hash, provider.clone(), 0, self.runtime.clone());
+        }
+        Ok(tx_hash)
+    }
+
+    /// Raise the fees suggested by the node by `gas_price_premium_percent`.
+    /// Fee fields the caller already set on `tx` are sent unchanged.
+    fn apply_gas_price_premium(&self, signer: &StylusSigner, tx: &mut TypedTransaction) -> Result<()> {
+        let premium = U256::from(self.gas_price_premium_percent.min(MAX_GAS_PRICE_PREMIUM_PERCENT));
+        let bump = |fee: U256| fee.saturating_add(fee.saturating_mul(premium) / 100);
+        let (suggested_fee, suggested_priority_fee) = match &*tx {
+            TypedTransaction::Eip1559(inner) => (inner.max_fee_per_gas.is_none(), inner.max_priority_fee_per_gas.is_none()),
+            _ => (tx.gas_price().is_none(), false),
+        };
+
+        self.fill_transaction(signer, tx)?;
+
+        match tx {
+            TypedTransaction::Eip1559(inner) => {
+                if suggested_fee {
+                    inner.max_fee_per_gas = inner.max_fee_per_gas.map(bump);
+                }
+                if suggested_priority_fee {
+                    inner.max_priority_fee_per_gas = inner.max_priority_fee_per_gas.map(bump);
+                }
+            }
+            _ => {
+                if let Some(gas_price) = tx.gas_price().filter(|_| suggested_fee) {
+                    tx.set_gas_price(bump(gas_price));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check up front that the wallet can pay for `tx`, failing with
+    /// `StylusError::InsufficientGasFunds { needed, have }` when it can't.
+    ///
+    /// Writes run this check themselves when `check_gas_funds` is enabled; call it
+    /// directly to check on demand, e.g. before enabling a button. `tx` is not sent.
+    pub fn check_funds(&self, tx: &TypedTransaction) -> Result<()> {
+        let signer = self
+            .contract_client
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Signer not initialized"))?;
+        self.ensure_gas_funds(signer, &mut tx.clone())
+    }
+
+    /// Fail with `StylusError::InsufficientGasFunds` unless the wallet covers
+    /// `gas * gas_price + value`. Fills the gas and fee fields of `tx` on the way,
+    /// so sending doesn't estimate them a second time.
+    fn ensure_gas_funds(&self, signer: &StylusSigner, tx: &mut TypedTransaction) -> Result<()> {
+        self.fill_transaction(signer, tx)?;
+        let have = self.block_on_rpc("eth_getBalance", signer.get_balance(signer.address(), None))?;
+        let needed = max_cost(tx);
+        if have < needed {
+            return Err(StylusError::InsufficientGasFunds { needed, have }.into());
+        }
+        Ok(())
+    }
+
+    /// Estimate gas and fees for whichever of them `tx` leaves unset
+    fn fill_transaction(&self, signer: &StylusSigner, tx: &mut TypedTransaction) -> Result<()> {
+        self.run_rpc("eth_estimateGas", signer.fill_transaction(tx, None))?
+            .map_err(|e| self.decode_provider_error("eth_estimateGas", e))
+    }
+}
+
+/// Most a filled transaction can cost in wei: `gas * gas_price + value`
+fn max_cost(tx: &TypedTransaction) -> U256 {
+    let gas = tx.gas().copied().unwrap_or_default();
+    gas.saturating_mul(tx.gas_price().unwrap_or_default())
+        .saturating_add(tx.value().copied().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{contract_address, MockNode};
+    use serde_json::{json, Value};
+
+    /// A legacy transfer with every field `fill_transaction` would otherwise look up
+    fn filled_tx(gas: u64, gas_price: u64, value: u64) -> TypedTransaction {
+        TransactionRequest::new()
+            .to(contract_address())
+            .gas(gas)
+            .gas_price(gas_price)
+            .value(value)
+            .nonce(0)
+            .into()
+    }
+
+    /// A node reporting `balance` wei for every account, failing any other call
+    fn node_with_balance(balance: u64) -> MockNode {
+        MockNode::start(move |method, _| match method {
+            "eth_getBalance" => Ok(Value::String(format!("{:#x}", balance))),
+            other => Err(ethers::providers::JsonRpcError {
+                code: -32601,
+                message: format!("unexpected {}", other),
+                data: Some(json!(null)),
+            }),
+        })
+    }
+
+    #[test]
+    fn max_cost_adds_value_to_the_gas_bill() {
+        assert_eq!(max_cost(&filled_tx(21_000, 2, 5)), U256::from(42_005));
+        assert_eq!(max_cost(&TransactionRequest::new().value(7).into()), U256::from(7));
+        let huge: TypedTransaction = TransactionRequest::new().gas(U256::MAX).gas_price(U256::MAX).value(1).into();
+        assert_eq!(max_cost(&huge), U256::MAX);
+    }
+
+    #[test]
+    fn check_funds_fails_when_the_wallet_cannot_pay() {
+        let client = node_with_balance(1_000).signing_client();
+        let error = client.check_funds(&filled_tx(21_000, 1, 0)).unwrap_err();
+        match error.downcast_ref::<StylusError>() {
+            Some(StylusError::InsufficientGasFunds { needed, have }) => {
+                assert_eq!((*needed, *have), (U256::from(21_000), U256::from(1_000)));
+            }
+            _ => panic!("expected InsufficientGasFunds, got {:#}", error),
+        }
+    }
+
+    #[test]
+    fn check_funds_passes_a_funded_wallet() {
+        let client = node_with_balance(21_000).signing_client();
+        client.check_funds(&filled_tx(21_000, 1, 0)).unwrap();
+    }
+
+    #[test]
+    fn check_funds_needs_a_signer() {
+        let error = StylusClient::default().check_funds(&filled_tx(21_000, 1, 0)).unwrap_err();
+        assert_eq!(error.to_string(), "Signer not initialized");
+    }
+}
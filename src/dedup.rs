@@ -0,0 +1,161 @@
+use ethers::types::{transaction::eip2718::TypedTransaction, Bytes, NameOrAddress, TxHash, U256};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Identifies a logical write: contract function, recipient, value and encoded call data
+pub(crate) type WriteKey = (String, Option<String>, U256, Bytes);
+
+/// The key of `tx` sent as `function`; writes of the same call to another contract
+/// or with another value stay distinct
+pub(crate) fn write_key(function: &str, tx: &TypedTransaction) -> WriteKey {
+    let to = tx.to().map(|to| match to {
+        NameOrAddress::Address(address) => format!("{:?}", address),
+        NameOrAddress::Name(name) => name.clone(),
+    });
+    (
+        function.to_string(),
+        to,
+        tx.value().copied().unwrap_or_default(),
+        tx.data().cloned().unwrap_or_default(),
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Submission {
+    InFlight,
+    Sent(TxHash),
+}
+
+/// How often a duplicate checks whether the original submission has finished
+const IN_FLIGHT_POLL: Duration = Duration::from_millis(20);
+
+/// Coalesces identical writes requested within a short window into one transaction.
+/// Disabled when no window is configured.
+#[derive(Debug, Clone, Default)]
+pub struct WriteDedup {
+    pub window: Option<Duration>,
+    recent: Arc<Mutex<HashMap<WriteKey, (Instant, Submission)>>>,
+}
+
+impl WriteDedup {
+    pub fn new(window: Option<Duration>) -> Self {
+        Self {
+            window,
+            recent: Arc::default(),
+        }
+    }
+
+    /// Reserve `key` for a new submission, or return the hash of an identical write
+    /// sent within the window. Waits while an identical write is still being submitted.
+    pub(crate) fn claim(&self, key: &WriteKey) -> Option<TxHash> {
+        let window = self.window?;
+        loop {
+            let mut recent = self.recent.lock().ok()?;
+            recent.retain(|_, (requested_at, _)| requested_at.elapsed() < window);
+            match recent.get(key) {
+                Some((_, Submission::Sent(hash))) => {
+                    println!("🔁 Deduplicated {} request, reusing transaction {:?}", key.0, hash);
+                    return Some(*hash);
+                }
+                Some((_, Submission::InFlight)) => {
+                    drop(recent);
+                    std::thread::sleep(IN_FLIGHT_POLL);
+                }
+                None => {
+                    recent.insert(key.clone(), (Instant::now(), Submission::InFlight));
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Forget every recent write, e.g. after switching networks
+    pub(crate) fn clear(&self) {
+        if let Ok(mut recent) = self.recent.lock() {
+            recent.clear();
+        }
+    }
+
+    /// Record the outcome of a claimed submission; failed writes release the key so they can be retried
+    pub(crate) fn finish(&self, key: &WriteKey, hash: Option<TxHash>) {
+        if self.window.is_none() {
+            return;
+        }
+        if let Ok(mut recent) = self.recent.lock() {
+            match hash {
+                Some(hash) => {
+                    if let Some((_, submission)) = recent.get_mut(key) {
+                        *submission = Submission::Sent(hash);
+                    }
+                }
+                None => {
+                    recent.remove(key);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Address, TransactionRequest};
+
+    fn tx(to: u64, value: u64) -> TypedTransaction {
+        TransactionRequest::new()
+            .to(Address::from_low_u64_be(to))
+            .value(value)
+            .data(vec![0xab, 0xcd])
+            .into()
+    }
+
+    #[test]
+    fn key_separates_recipient_and_value() {
+        let key = write_key("incrementSword", &tx(1, 0));
+        assert_eq!(key, write_key("incrementSword", &tx(1, 0)));
+        assert_ne!(key, write_key("incrementSword", &tx(2, 0)));
+        assert_ne!(key, write_key("incrementSword", &tx(1, 5)));
+        assert_ne!(key, write_key("transfer", &tx(1, 0)));
+    }
+
+    #[test]
+    fn sent_write_is_reused_within_window() {
+        let dedup = WriteDedup::new(Some(Duration::from_secs(60)));
+        let key = write_key("incrementSword", &tx(1, 0));
+        assert_eq!(dedup.claim(&key), None);
+        dedup.finish(&key, Some(TxHash::repeat_byte(7)));
+        assert_eq!(dedup.claim(&key), Some(TxHash::repeat_byte(7)));
+        assert_eq!(dedup.claim(&write_key("incrementSword", &tx(2, 0))), None);
+    }
+
+    #[test]
+    fn failed_write_releases_key() {
+        let dedup = WriteDedup::new(Some(Duration::from_secs(60)));
+        let key = write_key("incrementSword", &tx(1, 0));
+        assert_eq!(dedup.claim(&key), None);
+        dedup.finish(&key, None);
+        assert_eq!(dedup.claim(&key), None);
+    }
+
+    #[test]
+    fn expired_write_is_sent_again() {
+        let dedup = WriteDedup::new(Some(Duration::from_millis(10)));
+        let key = write_key("incrementSword", &tx(1, 0));
+        assert_eq!(dedup.claim(&key), None);
+        dedup.finish(&key, Some(TxHash::repeat_byte(7)));
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(dedup.claim(&key), None);
+    }
+
+    #[test]
+    fn disabled_without_window() {
+        let dedup = WriteDedup::new(None);
+        let key = write_key("incrementSword", &tx(1, 0));
+        assert_eq!(dedup.claim(&key), None);
+        dedup.finish(&key, Some(TxHash::repeat_byte(7)));
+        assert_eq!(dedup.claim(&key), None);
+    }
+}
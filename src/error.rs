@@ -0,0 +1,204 @@
+use ethers::abi::{self, Abi, ParamType, Token};
+use ethers::types::{Bytes, I256, U256};
+use std::{fmt, time::Duration};
+
+/// Selector of the builtin `Error(string)` revert
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of the builtin `Panic(uint256)` revert
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Typed failures raised by the Stylus client.
+///
+/// Client methods return `eyre::Result`; use `report.downcast_ref::<StylusError>()`
+/// to react to a specific failure.
+#[derive(Debug, Clone)]
+pub enum StylusError {
+    /// The client has no provider or contract, e.g. init failed or hasn't finished
+    NotInitialized,
+    /// The contract reverted the call
+    ContractRevert {
+        /// Name of the matched error (`Error` / `Panic` for the builtins), `None` if unknown
+        name: Option<String>,
+        /// Decoded error fields, in ABI order
+        params: Vec<Token>,
+        /// Raw revert data
+        data: Bytes,
+    },
+    /// A view call returned no (or too little) data, typically because the
+    /// contract isn't activated yet or the address is wrong
+    EmptyResponse { function: String },
+    /// The call did not complete within its `CallOptions::timeout`
+    Timeout { method: String, timeout: Duration },
+    /// The call did not complete before its `CallOptions::deadline`
+    Deadline { method: String },
+    /// The wallet can't pay for the transaction's gas and value (amounts in wei)
+    InsufficientGasFunds { needed: U256, have: U256 },
+    /// The estimated cost of a write is above the caller's spending cap (amounts in wei)
+    CostExceedsMax { cost: U256, max: U256 },
+    /// The node reported chain id 0, or one that differs from the configured `chain_id`
+    InvalidChainId { chain_id: u64, expected: Option<u64> },
+    /// `wait_for_block` gave up before the chain reached `target`
+    BlockTimeout { target: u64, last_seen: u64, timeout: Duration },
+    /// The endpoint is rate limiting requests. `retry_after` is the delay it asked for,
+    /// during which calls fail with this error without being sent.
+    RateLimited { retry_after: Option<Duration> },
+    /// A required `Stylus.toml` entry is absent, named by its dotted path (e.g. `contract.rpc_url`)
+    MissingConfigField { source: String, path: String },
+}
+
+impl StylusError {
+    /// `chain_id` if it is usable for signing: nonzero and equal to `expected` when given
+    pub(crate) fn check_chain_id(chain_id: u64, expected: Option<u64>) -> Result<u64, StylusError> {
+        if chain_id == 0 || expected.is_some_and(|expected| expected != chain_id) {
+            return Err(StylusError::InvalidChainId { chain_id, expected });
+        }
+        Ok(chain_id)
+    }
+}
+
+impl StylusError {
+    /// Decode revert data against the builtin errors and the custom errors defined in `abi`
+    pub fn from_revert_data(abi: &Abi, data: Bytes) -> Self {
+        let (name, params) = decode_revert(abi, &data)
+            .map(|(name, params)| (Some(name), params))
+            .unwrap_or((None, Vec::new()));
+        StylusError::ContractRevert { name, params, data }
+    }
+}
+
+fn decode_revert(abi: &Abi, data: &[u8]) -> Option<(String, Vec<Token>)> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, payload) = data.split_at(4);
+
+    if selector == ERROR_STRING_SELECTOR {
+        let params = abi::decode(&[ParamType::String], payload).ok()?;
+        return Some(("Error".to_string(), params));
+    }
+    if selector == PANIC_SELECTOR {
+        let params = abi::decode(&[ParamType::Uint(256)], payload).ok()?;
+        return Some(("Panic".to_string(), params));
+    }
+
+    abi.errors()
+        .find(|error| error.signature()[..4] == *selector)
+        .and_then(|error| Some((error.name.clone(), error.decode(payload).ok()?)))
+}
+
+impl fmt::Display for StylusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StylusError::NotInitialized => write!(f, "Contract not initialized"),
+            StylusError::ContractRevert { name: Some(name), params, .. } => match params.as_slice() {
+                [Token::String(reason)] if name == "Error" => write!(f, "Contract reverted: {}", reason),
+                [] => write!(f, "Contract reverted: {}", name),
+                _ => write!(f, "Contract reverted: {}({})", name, format_tokens(params)),
+            },
+            StylusError::ContractRevert { name: None, data, .. } => {
+                write!(f, "Contract reverted with data {}", data)
+            }
+            StylusError::EmptyResponse { function } => write!(
+                f,
+                "{} returned no data, is the contract activated and the address correct?",
+                function
+            ),
+            StylusError::Timeout { method, timeout } => write!(f, "{} timed out after {:?}", method, timeout),
+            StylusError::Deadline { method } => write!(f, "{} did not complete before its deadline", method),
+            StylusError::InsufficientGasFunds { needed, have } => {
+                write!(f, "Insufficient funds for gas: need {} wei, have {} wei", needed, have)
+            }
+            StylusError::CostExceedsMax { cost, max } => {
+                write!(f, "Estimated cost of {} wei exceeds the maximum of {} wei", cost, max)
+            }
+            StylusError::InvalidChainId { chain_id, expected: Some(expected) } => {
+                write!(f, "Node reports chain id {}, but chain_id {} is configured", chain_id, expected)
+            }
+            StylusError::InvalidChainId { chain_id, expected: None } => {
+                write!(f, "Invalid chain id {}, is the node configured correctly?", chain_id)
+            }
+            StylusError::BlockTimeout { target, last_seen, timeout } => {
+                write!(f, "Block {} not reached after {:?}, last seen block {}", target, timeout, last_seen)
+            }
+            StylusError::RateLimited { retry_after: Some(retry_after) } => {
+                write!(f, "RPC endpoint is rate limiting requests, retry after {:?}", retry_after)
+            }
+            StylusError::RateLimited { retry_after: None } => write!(f, "RPC endpoint is rate limiting requests"),
+            StylusError::MissingConfigField { source, path } => write!(f, "{} is missing `{}`", source, path),
+        }
+    }
+}
+
+impl std::error::Error for StylusError {}
+
+/// Render decoded ABI tokens for display, e.g. `5, 0x1234…, "gold"`
+pub fn format_tokens(tokens: &[Token]) -> String {
+    tokens.iter().map(format_token).collect::<Vec<_>>().join(", ")
+}
+
+fn format_token(token: &Token) -> String {
+    match token {
+        Token::Address(address) => format!("{:?}", address),
+        Token::FixedBytes(bytes) | Token::Bytes(bytes) => Bytes::from(bytes.clone()).to_string(),
+        Token::Int(value) => I256::from_raw(*value).to_string(),
+        Token::Uint(value) => value.to_string(),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => format!("{:?}", value),
+        Token::FixedArray(tokens) | Token::Array(tokens) => format!("[{}]", format_tokens(tokens)),
+        Token::Tuple(tokens) => format!("({})", format_tokens(tokens)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BLOCKCHAINCONTRACT_ABI;
+
+    fn revert_data(selector: [u8; 4], params: &[Token]) -> Bytes {
+        [selector.as_slice(), &abi::encode(params)].concat().into()
+    }
+
+    #[test]
+    fn unauthorized_reverts_decode_by_name() {
+        let selector = BLOCKCHAINCONTRACT_ABI.error("Unauthorized").unwrap().signature()[..4].try_into().unwrap();
+        let error = StylusError::from_revert_data(&BLOCKCHAINCONTRACT_ABI, revert_data(selector, &[]));
+        assert!(matches!(
+            &error,
+            StylusError::ContractRevert { name: Some(name), params, .. } if name == "Unauthorized" && params.is_empty()
+        ));
+        assert_eq!(error.to_string(), "Contract reverted: Unauthorized");
+    }
+
+    #[test]
+    fn builtin_reverts_decode() {
+        let reason = revert_data(ERROR_STRING_SELECTOR, &[Token::String("cooldown".to_string())]);
+        let error = StylusError::from_revert_data(&BLOCKCHAINCONTRACT_ABI, reason);
+        assert_eq!(error.to_string(), "Contract reverted: cooldown");
+
+        let panic = revert_data(PANIC_SELECTOR, &[Token::Uint(0x11.into())]);
+        let error = StylusError::from_revert_data(&BLOCKCHAINCONTRACT_ABI, panic);
+        assert!(matches!(error, StylusError::ContractRevert { name: Some(name), .. } if name == "Panic"));
+    }
+
+    #[test]
+    fn unknown_reverts_keep_the_raw_data() {
+        let data = revert_data([0xde, 0xad, 0xbe, 0xef], &[]);
+        let error = StylusError::from_revert_data(&BLOCKCHAINCONTRACT_ABI, data.clone());
+        assert!(matches!(&error, StylusError::ContractRevert { name: None, data: raw, .. } if *raw == data));
+        assert_eq!(error.to_string(), "Contract reverted with data 0xdeadbeef");
+    }
+
+    #[test]
+    fn check_chain_id_compares_with_expected() {
+        assert_eq!(StylusError::check_chain_id(421614, None).unwrap(), 421614);
+        assert_eq!(StylusError::check_chain_id(421614, Some(421614)).unwrap(), 421614);
+        assert!(matches!(
+            StylusError::check_chain_id(42161, Some(421614)),
+            Err(StylusError::InvalidChainId { chain_id: 42161, expected: Some(421614) })
+        ));
+        assert!(matches!(
+            StylusError::check_chain_id(0, None),
+            Err(StylusError::InvalidChainId { chain_id: 0, expected: None })
+        ));
+    }
+}
@@ -0,0 +1,407 @@
+use bevy::prelude::*;
+use ethers::contract::{parse_log, EthEvent};
+use ethers::prelude::{Middleware, Signer};
+use ethers::types::{Address, Filter, Log, TransactionReceipt, TxHash, H256, U64};
+use eyre::Result;
+use serde::Deserialize;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{StylusClient, StylusEvent, SwordIncrementedFilter};
+
+/// Which contract logs the event poller picks up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventFilterMode {
+    /// Only logs whose first indexed argument is our wallet, e.g. `SwordIncremented`'s player
+    #[default]
+    Own,
+    /// Every log emitted by the contract
+    All,
+}
+
+/// Emitted for each `SwordIncremented` log picked up by the event poller
+#[derive(Event, Debug, Clone)]
+pub struct SwordIncremented {
+    pub player: Address,
+    pub color: u64,
+    pub tx_hash: Option<TxHash>,
+    pub block_number: Option<u64>,
+}
+
+impl TryFrom<(SwordIncrementedFilter, &Log)> for SwordIncremented {
+    type Error = eyre::Report;
+
+    /// Fails when the color doesn't fit in a `u64` rather than keeping its low bits
+    fn try_from((event, log): (SwordIncrementedFilter, &Log)) -> Result<Self> {
+        let color = u64::try_from(event.color).map_err(|_| {
+            eyre::eyre!("SwordIncremented color {} in {:?} does not fit in a u64", event.color, log.transaction_hash)
+        })?;
+        Ok(SwordIncremented {
+            player: event.player,
+            color,
+            tx_hash: log.transaction_hash,
+            block_number: log.block_number.map(|block| block.as_u64()),
+        })
+    }
+}
+
+impl SwordIncremented {
+    /// Decoder registered for the built-in event, see [`StylusPlugin::on_event`](crate::StylusPlugin::on_event).
+    /// Logs with a color that doesn't fit in a `u64` are skipped with a warning.
+    pub fn decode_log(log: &Log) -> Option<Self> {
+        let event = parse_log::<SwordIncrementedFilter>(log.clone()).ok()?;
+        SwordIncremented::try_from((event, log))
+            .map_err(|e| println!("⚠️ Skipping {}", e))
+            .ok()
+    }
+}
+
+/// Every log the event poller picked up from the contract, before decoding
+#[derive(Event, Debug, Clone)]
+pub struct ContractLog(pub Log);
+
+/// Registers a log decoder and the system feeding it, see [`StylusPlugin::on_event`](crate::StylusPlugin::on_event)
+#[derive(Clone)]
+pub struct LogDecoderHook(pub(crate) Arc<dyn Fn(&mut App) + Send + Sync>);
+
+impl LogDecoderHook {
+    pub(crate) fn new<E: Event>(decoder: fn(&Log) -> Option<E>) -> Self {
+        LogDecoderHook(Arc::new(move |app: &mut App| {
+            // A second decoder for `E` replaces the first instead of emitting every event twice
+            if app.world().contains_resource::<LogDecoder<E>>() {
+                warn!("Replacing the log decoder already registered for {}", std::any::type_name::<E>());
+                app.insert_resource(LogDecoder { decoder });
+                return;
+            }
+            app.add_event::<E>()
+                .insert_resource(LogDecoder { decoder })
+                .add_systems(Update, decode_contract_logs::<E>.after(poll_contract_events));
+        }))
+    }
+}
+
+#[derive(Resource)]
+struct LogDecoder<E: Event> {
+    decoder: fn(&Log) -> Option<E>,
+}
+
+/// Turn this frame's `ContractLog`s into `E` with the registered decoder
+fn decode_contract_logs<E: Event>(decoder: Res<LogDecoder<E>>, mut logs: EventReader<ContractLog>, mut events: EventWriter<E>) {
+    events.send_batch(logs.read().filter_map(|ContractLog(log)| (decoder.decoder)(log)));
+}
+
+/// A contract log decoded against the ABI
+#[derive(Debug, Clone)]
+pub enum DecodedEvent {
+    SwordIncremented(SwordIncremented),
+}
+
+impl DecodedEvent {
+    /// Decode a log emitted by the contract, `None` if it matches no known event
+    pub fn decode(log: &Log) -> Option<Self> {
+        SwordIncremented::decode_log(log).map(DecodedEvent::SwordIncremented)
+    }
+}
+
+/// Largest block range requested in a single `eth_getLogs` call
+const LOG_CHUNK_SIZE: u64 = 10_000;
+
+/// Whether the node rejected a log query for matching too many results or blocks
+fn is_log_range_error(error: &eyre::Report) -> bool {
+    let message = format!("{:#}", error).to_lowercase();
+    ["more than 10000 results", "query returned more than", "too many results", "block range"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+type LogSlot = Arc<Mutex<Option<Result<(Vec<Log>, U64), String>>>>;
+
+#[derive(Resource, Default)]
+pub(crate) struct LogPoller {
+    since_last_poll: Duration,
+    /// First block not yet scanned, `None` until the first poll picks the current head
+    next_block: Option<U64>,
+    in_flight: Option<LogSlot>,
+}
+
+impl StylusClient {
+    /// Filter for the contract's `SwordIncremented` logs, honoring the configured filter mode.
+    /// `None` when the mode is `Own` but there is no wallet to filter on.
+    pub fn sword_incremented_filter(&self) -> Option<Filter> {
+        Some(self.contract_log_filter()?.topic0(SwordIncrementedFilter::signature()))
+    }
+
+    /// Filter for every log the event poller picks up from the contract, honoring the
+    /// configured filter mode. `None` when the mode is `Own` but there is no wallet to filter on.
+    pub fn contract_log_filter(&self) -> Option<Filter> {
+        let filter = Filter::new().address(self.contract_address?);
+        match self.event_filter {
+            EventFilterMode::All => Some(filter),
+            EventFilterMode::Own => {
+                let wallet = self.contract_client.as_ref()?.signer().address();
+                Some(filter.topic1(H256::from(wallet)))
+            }
+        }
+    }
+
+    /// Decode the contract events a transaction emitted, skipping logs from other contracts
+    pub fn decode_receipt_events(&self, receipt: &TransactionReceipt) -> Vec<DecodedEvent> {
+        receipt
+            .logs
+            .iter()
+            .filter(|log| Some(log.address) == self.contract_address)
+            .filter_map(DecodedEvent::decode)
+            .collect()
+    }
+
+    /// Fetch and decode the contract's events between two blocks (inclusive).
+    ///
+    /// Large ranges are queried in chunks, and chunks the node rejects for returning
+    /// too many results are split further.
+    pub fn get_events(&self, from_block: u64, to_block: u64) -> Result<Vec<DecodedEvent>> {
+        let address = self.contract_address.ok_or_else(|| eyre::eyre!("Contract not initialized"))?;
+        let logs = self.get_logs_chunked(&Filter::new().address(address), from_block, to_block)?;
+        Ok(logs.iter().filter_map(DecodedEvent::decode).collect())
+    }
+
+    /// Sword counts rebuilt by tallying every player's `SwordIncremented` logs from
+    /// `from_block` to the chain head, as a cross-check of `get_sword_counts` or a fallback
+    /// when the view is unreliable.
+    ///
+    /// Only matches the view when `from_block` is at or before the contract's deployment.
+    /// Logs are queried in chunks like [`get_events`](Self::get_events); colors outside 0..=2 are skipped.
+    pub fn reconstruct_counts_from_events(&self, from_block: u64) -> Result<(u64, u64, u64)> {
+        let provider = self
+            .provider
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Contract not initialized"))?;
+        let address = self.contract_address.ok_or_else(|| eyre::eyre!("Contract not initialized"))?;
+        let head = self.block_on_rpc("eth_blockNumber", provider.get_block_number())?.as_u64();
+        let filter = Filter::new().address(address).topic0(SwordIncrementedFilter::signature());
+
+        let mut counts = [0u64; 3];
+        for log in self.get_logs_chunked(&filter, from_block, head)? {
+            let Some(event) = SwordIncremented::decode_log(&log) else {
+                continue;
+            };
+            match counts.get_mut(event.color as usize) {
+                Some(count) => *count += 1,
+                None => println!("⚠️ Skipping SwordIncremented with unknown color {} in {:?}", event.color, log.transaction_hash),
+            }
+        }
+        let [a, b, c] = counts;
+        Ok((a, b, c))
+    }
+
+    /// `eth_getLogs` over `from_block..=to_block`, respecting node range limits
+    pub(crate) fn get_logs_chunked(&self, filter: &Filter, from_block: u64, to_block: u64) -> Result<Vec<Log>> {
+        let mut logs = Vec::new();
+        let mut start = from_block;
+        while start <= to_block {
+            let end = to_block.min(start.saturating_add(LOG_CHUNK_SIZE - 1));
+            logs.extend(self.get_logs_split(filter, start, end)?);
+            start = end + 1;
+        }
+        Ok(logs)
+    }
+
+    fn get_logs_split(&self, filter: &Filter, from_block: u64, to_block: u64) -> Result<Vec<Log>> {
+        let provider = self
+            .provider
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Contract not initialized"))?;
+        let range = filter.clone().from_block(from_block).to_block(to_block);
+        match self.block_on_rpc("eth_getLogs", provider.get_logs(&range)) {
+            Err(e) if from_block < to_block && is_log_range_error(&e) => {
+                let middle = from_block + (to_block - from_block) / 2;
+                let mut logs = self.get_logs_split(filter, from_block, middle)?;
+                logs.extend(self.get_logs_split(filter, middle + 1, to_block)?);
+                Ok(logs)
+            }
+            result => result,
+        }
+    }
+
+    /// Fetch matching logs from `from_block` to the chain head.
+    /// Returns the logs and the next block to scan.
+    fn poll_new_logs(&self, from_block: Option<U64>) -> Result<(Vec<Log>, U64)> {
+        let provider = self
+            .provider
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Contract not initialized"))?;
+        let head = self.block_on_rpc("eth_blockNumber", provider.get_block_number())?;
+
+        let from_block = match from_block {
+            Some(from_block) if from_block <= head => from_block,
+            Some(from_block) => return Ok((Vec::new(), from_block)),
+            // Only report events emitted after the poller started
+            None => return Ok((Vec::new(), head + 1)),
+        };
+        let Some(filter) = self.contract_log_filter() else {
+            return Ok((Vec::new(), head + 1));
+        };
+
+        let logs = self.get_logs_chunked(&filter, from_block.as_u64(), head.as_u64())?;
+        Ok((logs, head + 1))
+    }
+}
+
+/// Poll the contract's logs on `event_poll_interval` and emit them as `ContractLog`
+/// events for the registered decoders
+pub(crate) fn poll_contract_events(
+    client: Option<Res<StylusClient>>,
+    time: Res<Time>,
+    mut poller: ResMut<LogPoller>,
+    mut contract_logs: EventWriter<ContractLog>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+    let Some(interval) = client.event_poll_interval else {
+        return;
+    };
+
+    let finished = poller
+        .in_flight
+        .as_ref()
+        .and_then(|slot| slot.lock().ok().and_then(|mut result| result.take()));
+    if let Some(result) = finished {
+        poller.in_flight = None;
+        match result {
+            Ok((logs, next_block)) => {
+                poller.next_block = Some(next_block);
+                for log in logs {
+                    if let Some(event) = SwordIncremented::decode_log(&log) {
+                        client.event_stream.publish(StylusEvent::Log(event));
+                    }
+                    contract_logs.send(ContractLog(log));
+                }
+            }
+            Err(e) => println!("⚠️ Failed to poll contract events: {}", e),
+        }
+    }
+
+    poller.since_last_poll += time.delta();
+    if poller.since_last_poll < interval || poller.in_flight.is_some() {
+        return;
+    }
+    poller.since_last_poll = Duration::ZERO;
+
+    let slot: LogSlot = Arc::default();
+    poller.in_flight = Some(slot.clone());
+    let from_block = poller.next_block;
+    let client = client.clone();
+    std::thread::spawn(move || {
+        let result = client.poll_new_logs(from_block).map_err(|e| e.to_string());
+        if let Ok(mut slot) = slot.lock() {
+            *slot = Some(result);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{contract_address, encoded, MockNode};
+    use ethers::abi::{self, Token};
+    use ethers::types::U256;
+    use ethers::providers::JsonRpcError;
+    use ethers::utils::hex;
+    use serde_json::{json, Value};
+
+    #[derive(Event, Debug, PartialEq, Eq)]
+    struct Decoded(u8);
+
+    #[test]
+    fn registering_an_event_twice_decodes_once_with_the_last_decoder() {
+        let mut app = App::new();
+        app.add_event::<ContractLog>();
+        (LogDecoderHook::new(|_| Some(Decoded(1))).0)(&mut app);
+        (LogDecoderHook::new(|_| Some(Decoded(2))).0)(&mut app);
+
+        app.world_mut().send_event(ContractLog(Log::default()));
+        app.update();
+
+        let events = app.world().resource::<Events<Decoded>>();
+        let decoded: Vec<&Decoded> = events.iter_current_update_events().collect();
+        assert_eq!(decoded, [&Decoded(2)]);
+    }
+
+    /// `SwordIncremented` logs at blocks 1..=7 with colors 0, 1, 1, 2, 2, 2 and an unknown 7
+    const COLORS: [u64; 7] = [0, 1, 1, 2, 2, 2, 7];
+
+    fn sword_log(block: u64, color: impl Into<U256>) -> Value {
+        let player = H256::from(Address::repeat_byte(block as u8));
+        json!({
+            "address": format!("{:?}", contract_address()),
+            "topics": [format!("{:?}", SwordIncrementedFilter::signature()), format!("{:?}", player)],
+            "data": format!("0x{}", hex::encode(abi::encode(&[Token::Uint(color.into())]))),
+            "blockNumber": format!("{:#x}", block),
+            "transactionHash": format!("{:?}", TxHash::repeat_byte(block as u8)),
+        })
+    }
+
+    fn block_param(params: &Value, field: &str) -> u64 {
+        u64::from_str_radix(params[0][field].as_str().unwrap().trim_start_matches("0x"), 16).unwrap()
+    }
+
+    /// A node at block 20 serving [`COLORS`], rejecting log queries over more than `max_span` blocks.
+    /// Its `getSwordCounts` view agrees with the logs.
+    fn sword_node(max_span: u64) -> MockNode {
+        MockNode::start(move |method, params| match method {
+            "eth_blockNumber" => Ok(json!("0x14")),
+            "eth_call" => Ok(encoded(&[Token::Uint(1.into()), Token::Uint(2.into()), Token::Uint(3.into())])),
+            "eth_getLogs" => {
+                assert_eq!(params[0]["address"].as_str(), Some(format!("{:?}", contract_address()).as_str()));
+                let topic0 = params[0]["topics"][0].as_str().unwrap();
+                assert_eq!(topic0, format!("{:?}", SwordIncrementedFilter::signature()));
+                let (from, to) = (block_param(params, "fromBlock"), block_param(params, "toBlock"));
+                if to - from > max_span {
+                    return Err(JsonRpcError {
+                        code: -32000,
+                        message: "query returned more than 10000 results".to_string(),
+                        data: None,
+                    });
+                }
+                let logs = (1..).zip(COLORS).filter(|(block, _)| (from..=to).contains(block));
+                Ok(Value::Array(logs.map(|(block, color)| sword_log(block, color)).collect()))
+            }
+            other => panic!("unexpected {}", other),
+        })
+    }
+
+    #[test]
+    fn counts_are_tallied_from_sword_logs() {
+        assert_eq!(sword_node(u64::MAX).client().reconstruct_counts_from_events(0).unwrap(), (1, 2, 3));
+        assert_eq!(sword_node(u64::MAX).client().reconstruct_counts_from_events(4).unwrap(), (0, 0, 3));
+    }
+
+    #[test]
+    fn reconstructed_counts_match_the_view() {
+        let client = sword_node(u64::MAX).client();
+        assert_eq!(client.reconstruct_counts_from_events(0).unwrap(), client.get_sword_counts().unwrap());
+    }
+
+    #[test]
+    fn colors_beyond_u64_are_skipped_instead_of_truncated() {
+        // Low 64 bits are 1, which truncation would count as green
+        let color = (U256::one() << 64) + 1;
+        let log: Log = serde_json::from_value(sword_log(1, color)).unwrap();
+        assert!(SwordIncremented::decode_log(&log).is_none());
+        let log: Log = serde_json::from_value(sword_log(1, 1)).unwrap();
+        assert_eq!(SwordIncremented::decode_log(&log).map(|event| event.color), Some(1));
+    }
+
+    #[test]
+    fn oversized_log_queries_are_split() {
+        assert_eq!(sword_node(3).client().reconstruct_counts_from_events(0).unwrap(), (1, 2, 3));
+    }
+
+    #[test]
+    fn reconstructing_counts_needs_a_provider() {
+        assert!(StylusClient::default().reconstruct_counts_from_events(0).is_err());
+    }
+}
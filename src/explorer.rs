@@ -0,0 +1,34 @@
+use ethers::types::{Address, TxHash};
+
+use crate::StylusClient;
+
+/// Explorer used when `explorer_base_url` isn't configured, for known Arbitrum networks
+fn default_explorer(chain_id: u64) -> Option<&'static str> {
+    match chain_id {
+        42161 => Some("https://arbiscan.io"),
+        42170 => Some("https://nova.arbiscan.io"),
+        421614 => Some("https://sepolia.arbiscan.io"),
+        _ => None,
+    }
+}
+
+impl StylusClient {
+    /// Block explorer for the connected network, the configured one or a known default
+    fn explorer_base(&self) -> Option<&str> {
+        let base = match &self.explorer_base_url {
+            Some(base) => base.as_str(),
+            None => default_explorer(self.chain_id()?)?,
+        };
+        Some(base.trim_end_matches('/'))
+    }
+
+    /// Explorer page for a transaction, `None` when no explorer is known for the network
+    pub fn explorer_tx_url(&self, hash: TxHash) -> Option<String> {
+        Some(format!("{}/tx/{:?}", self.explorer_base()?, hash))
+    }
+
+    /// Explorer page for an address, e.g. the contract or a player
+    pub fn explorer_address_url(&self, address: Address) -> Option<String> {
+        Some(format!("{}/address/{:?}", self.explorer_base()?, address))
+    }
+}
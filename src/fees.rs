@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use ethers::prelude::Middleware;
+use ethers::types::{BlockNumber, U256};
+use eyre::Result;
+
+use crate::{StylusClient, StylusError, StylusRead};
+
+/// Decimals between wei and gwei
+const GWEI_DECIMALS: u8 = 9;
+
+/// Base fee of the latest block in wei, `None` on chains without EIP-1559.
+///
+/// Not registered by default; keep it current with
+/// `app.register_stylus_read::<BaseFee>(Some(interval))`.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BaseFee(pub Option<U256>);
+
+impl BaseFee {
+    /// The base fee in gwei for display, e.g. `"0.010000000"`
+    pub fn gwei(&self) -> Option<String> {
+        self.0.map(|fee| StylusClient::format_units(fee, GWEI_DECIMALS))
+    }
+}
+
+impl StylusRead for BaseFee {
+    const NAME: &'static str = "BaseFee";
+
+    fn fetch(client: &StylusClient) -> Result<Self> {
+        client.base_fee().map(BaseFee)
+    }
+}
+
+impl StylusClient {
+    /// `base_fee_per_gas` of the latest block in wei, `None` on chains without EIP-1559
+    pub fn base_fee(&self) -> Result<Option<U256>> {
+        let provider = self.provider.as_ref().ok_or(StylusError::NotInitialized)?;
+        let block = self.block_on_rpc("eth_getBlockByNumber", provider.get_block(BlockNumber::Latest))?;
+        Ok(block.and_then(|block| block.base_fee_per_gas))
+    }
+}
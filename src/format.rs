@@ -0,0 +1,169 @@
+use ethers::types::U256;
+use ethers::utils;
+use eyre::Result;
+use serde::Deserialize;
+
+use crate::StylusClient;
+
+/// Digit group separator for counts shown in the UI, `,` unless configured otherwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct ThousandsSeparator(pub char);
+
+impl Default for ThousandsSeparator {
+    fn default() -> Self {
+        ThousandsSeparator(',')
+    }
+}
+
+/// Render `value` with `separator` between groups of three digits, e.g. `1,234,567`
+pub fn format_count_with(value: u64, separator: char) -> String {
+    let digits = value.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            formatted.push(separator);
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Parse a count typed by the player, ignoring surrounding whitespace. `separator` may
+/// only appear between groups of three digits, as [`format_count_with`] places it, so
+/// `1,234` and `1234` parse but `1,2,3` doesn't.
+pub fn parse_count_with(input: &str, separator: char) -> Result<u64> {
+    let groups: Vec<&str> = input.trim().split(separator).collect();
+    let well_formed = groups.iter().all(|group| !group.is_empty() && group.chars().all(|c| c.is_ascii_digit()))
+        && (groups.len() == 1 || (groups[0].len() <= 3 && groups[1..].iter().all(|group| group.len() == 3)));
+    if !well_formed {
+        return Err(eyre::eyre!("Invalid count: {:?}", input));
+    }
+    let digits = groups.concat();
+    digits
+        .parse()
+        .map_err(|_| eyre::eyre!("Count out of range: {:?}", input))
+}
+
+impl StylusClient {
+    /// Render a count for the UI using the configured thousands separator
+    pub fn format_count(&self, value: u64) -> String {
+        format_count_with(value, self.thousands_separator.0)
+    }
+
+    /// Parse a count from a UI input field, the inverse of [`format_count`](Self::format_count)
+    pub fn parse_count(&self, input: &str) -> Result<u64> {
+        parse_count_with(input, self.thousands_separator.0)
+    }
+
+    /// Render a token amount with `decimals` decimals, e.g. `1500000` with 6 as `1.500000`.
+    /// Every decimal is kept, so nothing is rounded, and 0 decimals render as a plain integer.
+    /// Amounts with more than 77 decimals can't be represented and are shown unscaled.
+    pub fn format_units(value: U256, decimals: u8) -> String {
+        if decimals == 0 {
+            return value.to_string();
+        }
+        utils::format_units(value, u32::from(decimals)).unwrap_or_else(|_| value.to_string())
+    }
+
+    /// Parse a token amount with `decimals` decimals, the inverse of [`format_units`](Self::format_units).
+    /// Empty input and amounts with more decimals than the token has are rejected rather than rounded.
+    pub fn parse_units(input: &str, decimals: u8) -> Result<U256> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(eyre::eyre!("Invalid amount: empty input"));
+        }
+        if input.split_once('.').is_some_and(|(_, fraction)| fraction.len() > usize::from(decimals)) {
+            return Err(eyre::eyre!("Amount {:?} has more than {} decimals", input, decimals));
+        }
+        let amount = utils::parse_units(input, u32::from(decimals))
+            .map_err(|e| eyre::eyre!("Invalid amount {:?}: {}", input, e))?;
+        match amount {
+            utils::ParseUnits::U256(amount) => Ok(amount),
+            utils::ParseUnits::I256(_) => Err(eyre::eyre!("Amount can't be negative: {:?}", input)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_count_accepts_plain_and_grouped_digits() {
+        assert_eq!(parse_count_with("1234567", ',').unwrap(), 1_234_567);
+        assert_eq!(parse_count_with(" 1,234,567 ", ',').unwrap(), 1_234_567);
+        assert_eq!(parse_count_with("999", ',').unwrap(), 999);
+        assert_eq!(parse_count_with("0", ',').unwrap(), 0);
+        assert_eq!(parse_count_with("12.345", '.').unwrap(), 12_345);
+    }
+
+    #[test]
+    fn parse_count_rejects_misplaced_separators() {
+        for input in ["1,2,3", "12,34", "1234,567", ",123", "123,", "1,,234", "1,2345"] {
+            assert!(parse_count_with(input, ',').is_err(), "{:?} parsed", input);
+        }
+    }
+
+    #[test]
+    fn parse_count_rejects_empty_and_non_digits() {
+        for input in ["", "  ", ",", "-1", "12a", "1 234"] {
+            assert!(parse_count_with(input, ',').is_err(), "{:?} parsed", input);
+        }
+        assert!(parse_count_with("18446744073709551616", ',').is_err());
+    }
+
+    #[test]
+    fn parse_count_inverts_format_count() {
+        for value in [0, 7, 999, 1_000, 1_234_567, u64::MAX] {
+            assert_eq!(parse_count_with(&format_count_with(value, ','), ',').unwrap(), value);
+            assert_eq!(parse_count_with(&format_count_with(value, ' '), ' ').unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn format_units_keeps_every_decimal() {
+        assert_eq!(StylusClient::format_units(U256::from(1_500_000), 6), "1.500000");
+        assert_eq!(StylusClient::format_units(U256::from(1_999_999), 6), "1.999999");
+        assert_eq!(StylusClient::format_units(U256::from(1), 18), "0.000000000000000001");
+    }
+
+    #[test]
+    fn format_units_edge_cases() {
+        assert_eq!(StylusClient::format_units(U256::zero(), 6), "0.000000");
+        assert_eq!(StylusClient::format_units(U256::from(1_500_000), 0), "1500000");
+        // More decimals than a U256 can scale by: shown unscaled
+        assert_eq!(StylusClient::format_units(U256::from(15), 78), "15");
+    }
+
+    #[test]
+    fn parse_units_round_trips_format_units() {
+        for (value, decimals) in [(1_500_000u64, 6u8), (0, 6), (1, 18), (42, 0)] {
+            let formatted = StylusClient::format_units(U256::from(value), decimals);
+            assert_eq!(StylusClient::parse_units(&formatted, decimals).unwrap(), U256::from(value));
+        }
+    }
+
+    #[test]
+    fn parse_units_accepts_short_fractions() {
+        assert_eq!(StylusClient::parse_units(" 1.5 ", 6).unwrap(), U256::from(1_500_000));
+        assert_eq!(StylusClient::parse_units(".5", 6).unwrap(), U256::from(500_000));
+        assert_eq!(StylusClient::parse_units("0", 6).unwrap(), U256::zero());
+        assert_eq!(StylusClient::parse_units("0.0", 6).unwrap(), U256::zero());
+    }
+
+    #[test]
+    fn parse_units_rejects_too_many_decimals_instead_of_rounding() {
+        assert!(StylusClient::parse_units("1.9999999", 6).is_err());
+        assert!(StylusClient::parse_units("1.5", 0).is_err());
+        assert_eq!(StylusClient::parse_units("1.999999", 6).unwrap(), U256::from(1_999_999));
+    }
+
+    #[test]
+    fn parse_units_rejects_empty_negative_and_garbage() {
+        assert!(StylusClient::parse_units("", 6).is_err());
+        assert!(StylusClient::parse_units("   ", 6).is_err());
+        assert!(StylusClient::parse_units("-1", 6).is_err());
+        assert!(StylusClient::parse_units("1e3", 6).is_err());
+    }
+}
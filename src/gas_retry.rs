@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use ethers::types::{TxHash, U256};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use crate::{contract::SendOptions, StylusClient};
+
+/// Functions whose out-of-gas reverts are resubmitted with `auto_retry_out_of_gas`
+const RETRIED_FUNCTIONS: &[&str] = &["incrementSword"];
+
+/// Emitted when a write that ran out of gas is resubmitted once with a higher gas limit.
+/// The resubmission reports its own `TxConfirmed` / `TxFailed`.
+#[derive(Event, Debug, Clone)]
+pub struct TxGasRetried {
+    /// The transaction that ran out of gas
+    pub hash: TxHash,
+    /// Contract function that produced the transaction
+    pub function: String,
+    /// Gas limit of the failed transaction
+    pub old_gas_limit: U256,
+    /// Gas limit the resubmission is sent with
+    pub new_gas_limit: U256,
+}
+
+/// Resubmitted transactions, which are never retried a second time
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GasRetries(Arc<Mutex<HashSet<TxHash>>>);
+
+/// A reverted transaction used (nearly) all of its gas. Calls that run out of gas in a
+/// nested frame still leave 1/64 of the gas unspent, so this allows for that margin.
+pub(crate) fn is_out_of_gas(gas_used: U256, gas_limit: U256) -> bool {
+    !gas_limit.is_zero() && gas_used.saturating_mul(U256::from(64)) >= gas_limit.saturating_mul(U256::from(63))
+}
+
+impl StylusClient {
+    /// Resubmit `hash`, which ran out of gas, with its gas limit raised by
+    /// `out_of_gas_retry_bump_percent`, on a background thread.
+    ///
+    /// Only applies with `auto_retry_out_of_gas`, to increments, and to transactions that
+    /// are not themselves retries. Returns the retry to report, `None` when nothing was sent.
+    pub(crate) fn retry_out_of_gas(&self, hash: TxHash, gas_limit: U256) -> Option<TxGasRetried> {
+        if !self.auto_retry_out_of_gas {
+            return None;
+        }
+        if self.gas_retries.0.lock().map(|retries| retries.contains(&hash)).unwrap_or(true) {
+            println!("⚠️ Retry of {:?} ran out of gas again, not retrying", hash);
+            return None;
+        }
+        let entry = self.tx_history.find_by_hash(hash)?;
+        if !RETRIED_FUNCTIONS.contains(&entry.function.as_str()) {
+            return None;
+        }
+
+        let new_gas_limit = gas_limit.saturating_mul(U256::from(100 + self.out_of_gas_retry_bump_percent)) / 100;
+        let mut tx = entry.tx;
+        tx.set_gas(new_gas_limit);
+        println!("⛽ {} ran out of gas at {}, resubmitting with {}", entry.function, gas_limit, new_gas_limit);
+
+        let client = self.clone();
+        let function = entry.function.clone();
+        std::thread::spawn(move || {
+            // The failed write may still be inside the dedup window
+            let options = SendOptions { skip_dedup: true, ..Default::default() };
+            match client.send_transaction_with(&function, tx, options) {
+                Ok(retry) => {
+                    if let Ok(mut retries) = client.gas_retries.0.lock() {
+                        retries.insert(retry);
+                    }
+                }
+                Err(e) => println!("⚠️ Out-of-gas retry of {} failed: {}", function, e),
+            }
+        });
+        Some(TxGasRetried { hash, function: entry.function, old_gas_limit: gas_limit, new_gas_limit })
+    }
+}
@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+use ethers::types::{transaction::eip2718::TypedTransaction, TxHash};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+
+use crate::{StylusClient, StylusError};
+
+/// Where a write recorded in [`TxHistory`] ended up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxStatus {
+    Pending,
+    Confirmed,
+    Failed {
+        reason: String,
+        /// `false` for reverts, which would fail the same way again
+        retryable: bool,
+    },
+    /// Replaced by a no-op transaction through `cancel_tx`
+    Cancelled { replacement: TxHash },
+}
+
+/// A write submitted through the client, kept with its call so it can be retried
+#[derive(Debug, Clone)]
+pub struct TxHistoryEntry {
+    pub id: u64,
+    /// Contract function that produced the transaction
+    pub function: String,
+    /// The call as submitted, before nonce and fee filling
+    pub tx: TypedTransaction,
+    /// `None` when the write failed before it was broadcast
+    pub hash: Option<TxHash>,
+    pub status: TxStatus,
+}
+
+/// Send to resubmit the call of a failed, retryable [`TxHistory`] entry
+#[derive(Event, Debug, Clone, Copy)]
+pub struct RetryTx {
+    pub entry_id: u64,
+}
+
+/// Writes submitted this session, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct TxHistory {
+    entries: Arc<Mutex<Vec<TxHistoryEntry>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl TxHistory {
+    /// Snapshot of every recorded write
+    pub fn entries(&self) -> Vec<TxHistoryEntry> {
+        self.entries.lock().map(|entries| entries.clone()).unwrap_or_default()
+    }
+
+    pub fn get(&self, id: u64) -> Option<TxHistoryEntry> {
+        self.entries
+            .lock()
+            .ok()?
+            .iter()
+            .find(|entry| entry.id == id)
+            .cloned()
+    }
+
+    /// The entry of a broadcast transaction
+    pub(crate) fn find_by_hash(&self, hash: TxHash) -> Option<TxHistoryEntry> {
+        self.entries
+            .lock()
+            .ok()?
+            .iter()
+            .find(|entry| entry.hash == Some(hash))
+            .cloned()
+    }
+
+    /// Record the result of submitting `tx`
+    pub(crate) fn record(&self, function: &str, tx: TypedTransaction, result: &eyre::Result<TxHash>) {
+        let (hash, status) = match result {
+            Ok(hash) => (Some(*hash), TxStatus::Pending),
+            Err(e) => {
+                let reverted = matches!(e.downcast_ref::<StylusError>(), Some(StylusError::ContractRevert { .. }));
+                (None, TxStatus::Failed { reason: format!("{:#}", e), retryable: !reverted })
+            }
+        };
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(TxHistoryEntry { id, function: function.to_string(), tx, hash, status });
+        }
+    }
+
+    /// Forget every entry, e.g. after switching networks. Ids keep counting up.
+    pub(crate) fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    /// Update the entry of a broadcast transaction once it is mined or dropped
+    pub(crate) fn resolve(&self, hash: TxHash, status: TxStatus) {
+        if let Ok(mut entries) = self.entries.lock() {
+            if let Some(entry) = entries.iter_mut().find(|entry| entry.hash == Some(hash)) {
+                entry.status = status;
+            }
+        }
+    }
+}
+
+/// Resubmit the calls of entries named by `RetryTx` events on a background thread
+pub(crate) fn retry_failed_transactions(client: Option<Res<StylusClient>>, mut retries: EventReader<RetryTx>) {
+    let Some(client) = client else {
+        return;
+    };
+    for RetryTx { entry_id } in retries.read().copied() {
+        let Some(entry) = client.tx_history.get(entry_id) else {
+            println!("⚠️ Cannot retry unknown transaction history entry {}", entry_id);
+            continue;
+        };
+        match entry.status {
+            TxStatus::Failed { retryable: true, .. } => {}
+            TxStatus::Failed { retryable: false, .. } => {
+                println!("⚠️ Not retrying {} (entry {}): it reverted and would fail again", entry.function, entry_id);
+                continue;
+            }
+            _ => {
+                println!("⚠️ Not retrying {} (entry {}): it has not failed", entry.function, entry_id);
+                continue;
+            }
+        }
+
+        let client = client.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = client.send_transaction_as(&entry.function, entry.tx) {
+                println!("⚠️ Retry of {} (entry {}) failed: {}", entry.function, entry_id, e);
+            }
+        });
+    }
+}
@@ -1,28 +1,219 @@
 use bevy::prelude::*;
 use dotenv::dotenv;
-use ethers::prelude::{Provider, Http, SignerMiddleware, LocalWallet, abigen, Middleware};
+use ethers::prelude::{Provider, SignerMiddleware, LocalWallet, abigen, ContractCall, ContractError, Middleware, Multicall, MULTICALL_ADDRESS};
 use ethers::signers::Signer;
+use bevy::tasks::{IoTaskPool, Task};
 use eyre::Result;
-use std::{str::FromStr, sync::Arc, fs};
-use ethers::types::{Address, U256};
+use std::{collections::HashMap, future::Future, panic::AssertUnwindSafe, str::FromStr, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, fs, time::{Duration, Instant}};
+use ethers::types::{Address, BlockId, BlockNumber, Log, Transaction, TxHash, H256, U256};
+use ethers::utils::parse_ether;
 use serde::Deserialize;
-use toml;
+
+mod abi_check;
+
+mod api;
+pub use api::{MockStylusApi, StylusApi, StylusApiResource};
+
+mod balance;
+pub use balance::{BalanceChanged, SignerBalance};
+
+mod cache;
+
+mod calls;
+pub use calls::{token_to_json, CallOptions};
+
+mod contract;
+pub use contract::{GasConfig, StylusContract, StylusSigner};
+
+mod dedup;
+pub use dedup::WriteDedup;
+
+mod error;
+pub use error::{format_tokens, StylusError};
+
+mod events;
+pub use events::{ContractLog, DecodedEvent, EventFilterMode, LogDecoderHook, SwordIncremented};
+
+mod explorer;
+
+mod fees;
+pub use fees::BaseFee;
+
+mod format;
+pub use format::{format_count_with, parse_count_with, ThousandsSeparator};
+
+mod gas_retry;
+pub use gas_retry::TxGasRetried;
+
+mod history;
+pub use history::{RetryTx, TxHistory, TxHistoryEntry, TxStatus};
+
+mod network;
+pub use network::NetworkSwitched;
+
+mod networks;
+pub use networks::NetworkMismatch;
+
+mod pending;
+pub use pending::{
+    drain_pending_transactions, has_pending_txs, more_than_pending_txs, no_pending_txs, AsyncWriteId, ConfirmationPolicy,
+    PendingTransactions, PendingTx, TxCancelled, TxConfirmed, TxFailed,
+};
+
+mod players;
+
+mod private_tx;
+
+mod proxy;
+
+mod queue;
+pub use queue::SwordIncrementQueue;
+
+mod rate_limit;
+
+mod reads;
+pub use reads::{
+    ReadFallback, ReadStatus, ReadsRefreshed, RefreshReads, StylusAppExt, StylusRead, StylusStateRefreshed, SwordCounts,
+};
+
+mod refresh;
+pub use refresh::{IncrementAndRefresh, IncrementRefreshed};
+
+mod relayer;
+pub use relayer::{RelayerConfig, RelayerFormat};
+
+mod runtime;
+pub use runtime::StylusRuntime;
+
+mod sequential;
+pub use sequential::{NonceResynced, SequentialWriteQueue};
+
+mod signing;
+pub use signing::MessageFormat;
+
+mod snapshot;
+pub use snapshot::{StateSnapshot, ValueChange};
+
+mod spend;
+pub use spend::{GasStats, SessionSpend};
+
+mod stream;
+pub use stream::StylusEvent;
+
+mod strict;
+
+#[cfg(test)]
+mod test_support;
+
+#[cfg(feature = "erc20")]
+mod token;
+#[cfg(feature = "erc20")]
+pub use token::Erc20;
+
+mod transport;
+pub use transport::{StylusHttp, StylusHttpError};
+
+mod watchdog;
+pub use watchdog::{drain_rpc_watchdog, RpcWatchdog, SlowRpc, SlowRpcAction, StylusErrorLog, StylusMetrics};
+
+#[cfg(target_arch = "wasm32")]
+mod browser_wallet;
+#[cfg(target_arch = "wasm32")]
+pub use browser_wallet::{BrowserWallet, Eip1193, Eip1193Error};
 
 #[derive(Debug, Deserialize)]
 struct StylusConfig {
+    #[serde(default)]
     contract: ContractConfig,
     deployment: DeploymentConfig,
     functions: FunctionsConfig,
+    #[serde(default)]
+    options: OptionsConfig,
+    relayer: Option<RelayerConfig>,
+    /// Per-network `address` / `rpc_url` / `chain_id`, applied over `[contract]`
+    #[serde(default)]
+    networks: HashMap<String, networks::NetworkConfig>,
+    /// `[networks.<name>]` entry to connect to, `[contract]` alone when unset
+    active_network: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct ContractConfig {
-    address: String,
-    network: String,
-    rpc_url: String,
+type SharedProvider = Arc<Provider<StylusHttp>>;
+
+/// The `[contract]` section of `Stylus.toml`, also accepted by `StylusClient::switch_network`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ContractConfig {
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub network: String,
+    /// Endpoint for both reads and writes, unless overridden below
+    #[serde(default)]
+    pub rpc_url: String,
+    /// Endpoint for view calls, receipts and logs, e.g. a fast or cheap read node
+    pub read_rpc_url: Option<String>,
+    /// Endpoint transactions are broadcast through
+    pub write_rpc_url: Option<String>,
+    /// Multicall3 deployment used for batched calls, defaults to the canonical address
+    pub multicall_address: Option<String>,
+    /// Known chain id; skips the startup `eth_chainId` query and is verified on the first call
+    /// instead, which fails when the node reports another one
+    pub chain_id: Option<u64>,
+    /// Block explorer for this network, e.g. `https://arbiscan.io`. Known Arbitrum
+    /// networks get a default based on the chain id.
+    pub explorer_base_url: Option<String>,
+    /// Endpoint accepting `eth_sendPrivateTransaction`; writes are sent through it
+    /// instead of the public mempool
+    pub private_tx_url: Option<String>,
+    /// `address` is an EIP-1967 proxy: look up its implementation for diagnostics.
+    /// Calls still go to the proxy, using the implementation's ABI.
+    #[serde(default)]
+    pub resolve_proxy: bool,
+    /// ERC-20 token the game uses, for the `token_*` methods of the `erc20` feature
+    pub token_address: Option<String>,
+}
+
+impl ContractConfig {
+    fn multicall_address(&self) -> Result<Address> {
+        match &self.multicall_address {
+            Some(address) => address
+                .parse()
+                .map_err(|e| eyre::eyre!("Invalid multicall_address {}: {}", address, e)),
+            None => Ok(MULTICALL_ADDRESS),
+        }
+    }
+
+    /// Endpoint used for reads: `read_rpc_url`, else `rpc_url`, else `write_rpc_url`
+    fn read_endpoint(&self) -> &str {
+        self.endpoint(&self.read_rpc_url, &self.write_rpc_url)
+    }
+
+    /// Endpoint used for writes: `write_rpc_url`, else `rpc_url`, else `read_rpc_url`
+    fn write_endpoint(&self) -> &str {
+        self.endpoint(&self.write_rpc_url, &self.read_rpc_url)
+    }
+
+    fn endpoint<'a>(&'a self, preferred: &'a Option<String>, other: &'a Option<String>) -> &'a str {
+        preferred
+            .as_deref()
+            .or(Some(self.rpc_url.as_str()).filter(|url| !url.is_empty()))
+            .or(other.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Providers for the read and write endpoints, shared when they are the same
+    fn providers(&self, client: &StylusClient) -> Result<(SharedProvider, SharedProvider)> {
+        let read = Arc::new(client.http_provider(self.read_endpoint())?);
+        let write = if self.write_endpoint() == self.read_endpoint() {
+            read.clone()
+        } else {
+            Arc::new(client.http_provider(self.write_endpoint())?)
+        };
+        Ok((read, write))
+    }
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct DeploymentConfig {
     tx_hash: String,
     activation_tx_hash: String,
@@ -36,23 +227,499 @@ struct FunctionsConfig {
     signatures: Vec<String>,
 }
 
+/// Named private keys, kept in their own file so `Stylus.toml` can be committed
+#[derive(Deserialize)]
+struct KeysFile {
+    keys: std::collections::HashMap<String, String>,
+}
+
+/// Private key of `profile` from `path`; the error never includes key material
+fn load_profile_key(path: &str, profile: &str) -> Result<String> {
+    let content = fs::read_to_string(path).map_err(|e| eyre::eyre!("Failed to read {}: {}", path, e))?;
+    let mut keys_file: KeysFile = toml::from_str(&content)
+        .map_err(|_| eyre::eyre!("Failed to parse {}: expected a [keys] table of profile = \"0x...\" entries", path))?;
+    keys_file
+        .keys
+        .remove(profile)
+        .ok_or_else(|| eyre::eyre!("Key profile {:?} not found in {}", profile, path))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct OptionsConfig {
+    /// How often pending transactions are polled for confirmation, in milliseconds.
+    /// Lower values give faster feedback on fast chains (e.g. Arbitrum) at the cost
+    /// of more RPC requests; raise it on slow chains to avoid wasteful polling.
+    confirmation_poll_interval_ms: u64,
+    /// Calls slower than this many milliseconds are flagged by the RPC watchdog (disabled when unset)
+    slow_rpc_threshold_ms: Option<u64>,
+    /// Whether slow calls are logged or emitted as `SlowRpc` events
+    slow_rpc_action: SlowRpcAction,
+    /// Issue a cheap request during init so the first gameplay call doesn't pay connection setup
+    /// (init runs on the same runtime as later calls, so the warmed connection stays pooled)
+    prewarm: bool,
+    /// File where in-flight transactions are saved so they can be re-checked after a crash
+    pending_tx_file: Option<String>,
+    /// Default auto-poll interval for registered reads such as `SwordCounts` (manual refresh only when unset)
+    read_poll_interval_ms: Option<u64>,
+    /// How often contract logs are polled for events (event polling disabled when unset)
+    event_poll_interval_ms: Option<u64>,
+    /// Whether to emit events for our own wallet's logs only, or for every player's
+    event_filter: EventFilterMode,
+    /// Block tag view calls are evaluated at
+    read_block_tag: ReadBlockTag,
+    /// Print the human-friendly emoji banner at startup; structured `info!` lines are always emitted
+    startup_banner: bool,
+    /// Warn during init when the configured contract address has no code
+    check_contract_code: bool,
+    /// Probe every ABI and `[functions]` selector during init, warning on the ones that seem missing
+    validate_abi: bool,
+    /// Simulate `increment_sword_async` with an `eth_call` before sending, reporting reverts
+    /// early through `TxFailed` at the cost of an extra round-trip
+    simulate_async_writes: bool,
+    /// Digit group separator for counts shown in the UI
+    thousands_separator: ThousandsSeparator,
+    /// Check the signer's balance covers gas and value before each write (one extra RPC per write)
+    check_gas_funds: bool,
+    /// How often the signer's balance is polled for `BalanceChanged` events (disabled when unset)
+    balance_poll_interval_ms: Option<u64>,
+    /// Pay this many percent above the node's suggested gas price / priority fee for faster inclusion
+    gas_price_premium_percent: u32,
+    /// Idle keep-alive connections kept open to the RPC host
+    http_pool_max_idle_per_host: usize,
+    /// How long an idle pooled connection is kept before closing, in milliseconds
+    http_pool_idle_timeout_ms: u64,
+    /// TCP keep-alive interval for RPC connections, in milliseconds (disabled when unset)
+    http_tcp_keepalive_ms: Option<u64>,
+    /// Identical writes (same function, contract, value and arguments) requested within this many
+    /// milliseconds are sent once (dedup disabled when unset)
+    dedup_window_ms: Option<u64>,
+    /// Confirmation depth by transaction value, e.g. wait longer before reporting a purchase
+    confirmation_thresholds: Vec<ConfirmationThresholdConfig>,
+    /// How many recent RPC failures `StylusErrorLog` keeps (32 when unset)
+    error_log_size: Option<usize>,
+    /// Give up on connecting after this many milliseconds and start disconnected
+    init_timeout_ms: u64,
+    /// Sign with this profile from `keys_file` instead of `PRIVATE_KEY`; `STYLUS_PROFILE` overrides it
+    key_profile: Option<String>,
+    /// File holding the `[keys]` table of named private keys
+    keys_file: String,
+    /// Submit a frame's `SwordIncrementQueue` requests as one multicall transaction
+    coalesce_increments: bool,
+    /// Start `SessionSpend` from zero after `switch_network`
+    reset_session_spend_on_switch: bool,
+    /// Number of recent confirmations `GasStats` averages over
+    gas_stats_window: usize,
+    /// Route `SwordIncrementQueue` through `SequentialWriteQueue` so increments land in order
+    sequential_writes: bool,
+    /// Wait for each sequential write to be mined, not just broadcast, before the next
+    sequential_await_receipt: bool,
+    /// Events buffered per `subscribe_events` receiver (channel disabled when unset)
+    event_channel_capacity: Option<usize>,
+    /// Reject keys no section or option reads, e.g. a misspelled `rpc_ur`
+    strict_config: bool,
+    /// Resubmit an increment that ran out of gas once, with a higher gas limit
+    auto_retry_out_of_gas: bool,
+    /// Percent the gas limit of an out-of-gas retry is raised by
+    out_of_gas_retry_bump_percent: u32,
+    /// Times a request answered with HTTP 429 is sent again before failing as rate limited
+    rate_limit_retries: u32,
+    /// Longest `Retry-After` delay waited out before a retry; longer ones fail right away
+    rate_limit_max_wait_ms: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfirmationThresholdConfig {
+    /// Applies to transactions sending at least this much ETH, e.g. `"0.01"`
+    min_value_ether: String,
+    /// Blocks on top of the including one to wait for
+    depth: usize,
+}
+
+impl Default for OptionsConfig {
+    fn default() -> Self {
+        Self {
+            confirmation_poll_interval_ms: 1000,
+            slow_rpc_threshold_ms: None,
+            slow_rpc_action: SlowRpcAction::Log,
+            prewarm: true,
+            pending_tx_file: None,
+            read_poll_interval_ms: None,
+            event_poll_interval_ms: None,
+            event_filter: EventFilterMode::Own,
+            read_block_tag: ReadBlockTag::Latest,
+            startup_banner: true,
+            check_contract_code: true,
+            validate_abi: false,
+            simulate_async_writes: false,
+            thousands_separator: ThousandsSeparator::default(),
+            check_gas_funds: false,
+            balance_poll_interval_ms: None,
+            gas_price_premium_percent: 0,
+            http_pool_max_idle_per_host: 16,
+            http_pool_idle_timeout_ms: 90_000,
+            http_tcp_keepalive_ms: Some(60_000),
+            dedup_window_ms: None,
+            confirmation_thresholds: Vec::new(),
+            error_log_size: None,
+            init_timeout_ms: 10_000,
+            key_profile: None,
+            keys_file: "Stylus.keys.toml".to_string(),
+            coalesce_increments: false,
+            reset_session_spend_on_switch: true,
+            gas_stats_window: spend::DEFAULT_GAS_STATS_WINDOW,
+            sequential_writes: false,
+            sequential_await_receipt: false,
+            event_channel_capacity: None,
+            strict_config: false,
+            auto_retry_out_of_gas: false,
+            out_of_gas_retry_bump_percent: 50,
+            rate_limit_retries: 3,
+            rate_limit_max_wait_ms: 10_000,
+        }
+    }
+}
+
+impl OptionsConfig {
+    /// HTTP client shared by every RPC request, with the configured connection pooling
+    fn http_client(&self) -> Result<reqwest::Client> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let client = reqwest::Client::builder()
+            .pool_max_idle_per_host(self.http_pool_max_idle_per_host)
+            .pool_idle_timeout(Duration::from_millis(self.http_pool_idle_timeout_ms))
+            .tcp_keepalive(self.http_tcp_keepalive_ms.map(Duration::from_millis))
+            .build()?;
+        // Browsers manage connections themselves
+        #[cfg(target_arch = "wasm32")]
+        let client = reqwest::Client::new();
+        Ok(client)
+    }
+}
+
+/// Block tag used for view calls.
+///
+/// `Safe` and `Finalized` avoid flicker from reorgs at the cost of lagging behind
+/// the head. On Arbitrum Nitro chains (One, Nova, Sepolia) they follow the parent
+/// chain's safe/finalized blocks and can trail `Latest` by several minutes. Nodes
+/// that don't support a tag (some local dev nodes) fall back to `Latest` with a warning.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadBlockTag {
+    #[default]
+    Latest,
+    Safe,
+    Finalized,
+    Pending,
+}
+
+impl From<ReadBlockTag> for BlockNumber {
+    fn from(tag: ReadBlockTag) -> Self {
+        match tag {
+            ReadBlockTag::Latest => BlockNumber::Latest,
+            ReadBlockTag::Safe => BlockNumber::Safe,
+            ReadBlockTag::Finalized => BlockNumber::Finalized,
+            ReadBlockTag::Pending => BlockNumber::Pending,
+        }
+    }
+}
+
+/// Whether a failed call looks like the node rejecting the requested block tag
+fn is_unsupported_block_tag(error: &eyre::Report) -> bool {
+    if error.downcast_ref::<StylusError>().is_some() {
+        return false;
+    }
+    let message = format!("{:#}", error).to_lowercase();
+    message.contains("block")
+        && ["not found", "unsupported", "not supported", "invalid", "unknown"]
+            .iter()
+            .any(|pattern| message.contains(pattern))
+}
+
 // Generate the contract bindings
 abigen!(
     BlockchainContract,
     r#"[
         function getSwordCounts() external view returns (uint256, uint256, uint256)
         function incrementSword(uint256 color) external
+        function canIncrement(address user) external view returns (bool)
+        function swordsByPlayer(address player) external view returns (uint256, uint256, uint256)
+        event SwordIncremented(address indexed player, uint256 color)
+        error Unauthorized()
     ]"#
 );
 
-#[derive(Resource, Clone)]
+/// What node the client is talking to, for diagnostics and bug reports.
+/// Fields are `None` when the node doesn't implement the corresponding method.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeInfo {
+    /// `web3_clientVersion`, e.g. `nitro/v3.1.0`
+    pub client_version: Option<String>,
+    /// `net_version`
+    pub net_version: Option<String>,
+}
+
+/// Emitted when init could not load a signer and the client fell back to read-only mode.
+#[derive(Event, Debug, Clone)]
+pub struct StylusReadOnlyFallback {
+    pub reason: String,
+}
+
+/// Emitted once init has finished, whether or not it connected
+#[derive(Event, Debug, Clone)]
+pub struct StylusReady {
+    /// Why init failed, `None` when the client connected
+    pub error: Option<String>,
+}
+
+/// Run condition that holds once init has finished and `StylusClient` is available.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_stylus_plugin::stylus_ready;
+/// # fn show_counts() {}
+/// App::new().add_systems(Update, show_counts.run_if(stylus_ready));
+/// ```
+pub fn stylus_ready(client: Option<Res<StylusClient>>) -> bool {
+    client.is_some()
+}
+
+#[derive(Resource, Clone, Default)]
 pub struct StylusClient {
-    pub contract_client: Option<Arc<SignerMiddleware<Provider<Http>, LocalWallet>>>,
+    /// The wallet's signer middleware, built on `write_provider`
+    pub contract_client: Option<Arc<SignerMiddleware<Provider<StylusHttp>, LocalWallet>>>,
     pub contract_address: Option<Address>,
-    pub contract: Option<BlockchainContract<SignerMiddleware<Provider<Http>, LocalWallet>>>,
+    /// The contract bound to `contract_client`, used for writes such as `increment_sword`
+    pub contract: Option<BlockchainContract<SignerMiddleware<Provider<StylusHttp>, LocalWallet>>>,
+    /// Provider for view calls, receipts and logs
+    pub provider: Option<Arc<Provider<StylusHttp>>>,
+    /// Provider transactions are sent through, the same as `provider` unless `write_rpc_url` is set
+    pub write_provider: Option<Arc<Provider<StylusHttp>>>,
+    /// The contract bound to `provider`, used for view calls such as `get_sword_counts`
+    pub read_contract: Option<BlockchainContract<Provider<StylusHttp>>>,
+    /// Why writes are disabled, if the client is running in read-only mode
+    pub read_only_reason: Option<String>,
+    /// Polling cadence used while waiting for transaction confirmations
+    pub confirmation_poll_interval: Duration,
+    /// Multicall3 contract used to batch calls on this network
+    pub multicall_address: Option<Address>,
+    /// Game token from `token_address`, `None` when unset
+    #[cfg(feature = "erc20")]
+    pub token_address: Option<Address>,
+    pub rpc_watchdog: RpcWatchdog,
+    /// Coalesces identical writes requested in quick succession
+    pub write_dedup: WriteDedup,
+    /// Submitted transactions that are still waiting to be mined
+    pub pending_transactions: PendingTransactions,
+    /// Every write submitted this session, with its outcome
+    pub tx_history: TxHistory,
+    /// Confirmation depth for writes, by the ETH value they send
+    pub confirmation_policy: ConfirmationPolicy,
+    /// Default auto-poll interval for registered reads
+    pub read_poll_interval: Option<Duration>,
+    /// How often contract logs are polled for events, `None` disables event polling
+    pub event_poll_interval: Option<Duration>,
+    /// How often the signer's balance is polled, `None` disables the balance watcher
+    pub balance_poll_interval: Option<Duration>,
+    pub event_filter: EventFilterMode,
+    /// Block tag view calls are evaluated at
+    pub read_block_tag: ReadBlockTag,
+    /// Simulate fire-and-forget writes with `eth_call` first so reverts surface as `TxFailed`
+    pub simulate_async_writes: bool,
+    /// Check the wallet can pay for gas before sending, failing with `StylusError::InsufficientGasFunds`
+    pub check_gas_funds: bool,
+    /// Percentage added to node-suggested gas fees on writes, clamped to 100
+    pub gas_price_premium_percent: u32,
+    /// Digit group separator used by `format_count` / `parse_count`
+    pub thousands_separator: ThousandsSeparator,
+    /// Whether a frame's queued increments are sent as one multicall transaction
+    pub coalesce_increments: bool,
+    /// Whether `SessionSpend` is reset when the client switches networks
+    pub reset_session_spend_on_switch: bool,
+    /// Number of recent confirmations `GasStats` averages over
+    pub gas_stats_window: usize,
+    /// Whether queued increments are sent one at a time, in order
+    pub sequential_writes: bool,
+    /// Whether each sequential write waits for its receipt before the next is sent
+    pub sequential_await_receipt: bool,
+    /// Whether increments that ran out of gas are resubmitted once with more gas
+    pub auto_retry_out_of_gas: bool,
+    /// Percent the gas limit is raised by when retrying an out-of-gas increment
+    pub out_of_gas_retry_bump_percent: u32,
+    /// Nonce the next sequential write should get, as counted locally since the last send
+    sequential_nonce: Arc<Mutex<Option<u64>>>,
+    /// Out-of-gas resubmissions, so each write is retried at most once
+    gas_retries: gas_retry::GasRetries,
+    /// Set once the node rejected `read_block_tag`, reads then use `latest`
+    read_block_tag_unsupported: Arc<AtomicBool>,
+    /// Chain id of the connected network, `None` while disconnected
+    chain_id: Option<u64>,
+    /// Configured block explorer, overriding the default for the chain id
+    pub explorer_base_url: Option<String>,
+    /// Relayer writes are submitted through instead of being sent directly
+    pub relayer: Option<RelayerConfig>,
+    /// Nonce of the last intent the signer sent to the relayer
+    relay_nonce: relayer::RelayNonce,
+    /// Private transaction endpoint writes go through before the public mempool
+    pub private_tx_url: Option<String>,
+    /// Implementation behind the configured proxy, set when `resolve_proxy` is enabled
+    pub implementation_address: Option<Address>,
+    /// Chain id taken from config that is checked against the node on the first call
+    chain_id_check: Option<Arc<network::ChainIdCheck>>,
+    /// Transaction that activated the Stylus program, from `[deployment]`
+    pub activation_tx_hash: Option<TxHash>,
+    /// Function signatures listed under `[functions]` in `Stylus.toml`
+    function_signatures: Vec<String>,
+    /// Runtime blocking calls and confirmation watchers run on
+    runtime: StylusRuntime,
+    /// Channel behind `subscribe_events`
+    event_stream: stream::EventStream,
+    /// Disagreement between the configured `network` name and the chain id, found during init
+    network_mismatch: Option<NetworkMismatch>,
+    /// `[networks.<name>]` entries resolved against `[contract]`, for `switch_to_network`
+    network_configs: HashMap<String, ContractConfig>,
+    /// `chain_id`s of the `[networks]` entries, checked against `network` on init and switches
+    declared_chain_ids: HashMap<String, u64>,
+    /// The `[contract]` settings the client is connected with, see `contract_config`
+    contract_config: Option<ContractConfig>,
+    /// Last `get_sword_counts_fresh` result and the block it was read at
+    sword_counts_cache: cache::BlockCache<(u64, u64, u64)>,
+    /// Set while an endpoint's `Retry-After` delay is running; calls fail fast until it ends
+    rate_limit_cooldown: rate_limit::RateLimitCooldown,
+    /// Pooled HTTP client every provider built by the client sends its requests through
+    http_client: reqwest::Client,
+    /// How providers built by the client retry requests answered with HTTP 429
+    rate_limit_retry: transport::RateLimitRetry,
+    /// Completed `switch_network` calls not yet announced as `NetworkSwitched` events
+    network_switches: network::NetworkSwitchQueue,
+    #[cfg(target_arch = "wasm32")]
+    pub browser_wallet: Option<BrowserWallet>,
+    #[cfg(target_arch = "wasm32")]
+    pub browser_contract: Option<BlockchainContract<Provider<Eip1193>>>,
 }
 
 impl StylusClient {
+    /// Chain id of the connected network, cached at init so it isn't re-fetched.
+    /// `None` while disconnected.
+    pub fn chain_id(&self) -> Option<u64> {
+        self.chain_id
+    }
+
+    /// The effective `[contract]` settings: those from `Stylus.toml` with the active
+    /// `[networks.<name>]` entry applied, or the ones passed to the last `switch_network`.
+    /// `None` until init succeeded.
+    pub fn contract_config(&self) -> Option<&ContractConfig> {
+        self.contract_config.as_ref()
+    }
+
+    /// Provider for `rpc_url` that shares the client's connection pool and polling cadence
+    fn http_provider(&self, rpc_url: &str) -> Result<Provider<StylusHttp>> {
+        let url = reqwest::Url::parse(rpc_url).map_err(|e| eyre::eyre!("Invalid rpc_url {}: {}", rpc_url, e))?;
+        let transport = StylusHttp::new(url, self.http_client.clone(), self.rate_limit_retry);
+        Ok(Provider::new(transport).interval(self.confirmation_poll_interval))
+    }
+
+    /// Run a future to completion on the client's runtime, recording its latency
+    fn run_rpc<F: Future>(&self, method: &str, future: F) -> Result<F::Output> {
+        if let Some(remaining) = self.rate_limit_cooldown.remaining() {
+            return Err(StylusError::RateLimited { retry_after: Some(remaining) }.into());
+        }
+        self.verify_chain_id()?;
+        let started = Instant::now();
+        let output = self.runtime.block_on(future)?;
+        self.rpc_watchdog.record(method, started.elapsed());
+        Ok(output)
+    }
+
+    /// Compare a configured chain id with the node's, once. A node on another chain,
+    /// or reporting chain 0, fails this and every later call with `StylusError::InvalidChainId`.
+    fn verify_chain_id(&self) -> Result<()> {
+        let (Some(check), Some(provider)) = (&self.chain_id_check, &self.provider) else {
+            return Ok(());
+        };
+        if let Some(outcome) = check.outcome.get() {
+            return Ok(outcome.clone()?);
+        }
+        match self.runtime.block_on(provider.get_chainid())? {
+            Ok(actual) => {
+                let outcome = StylusError::check_chain_id(actual.as_u64(), Some(check.expected)).map(drop);
+                if let Err(e) = &outcome {
+                    println!("⚠️ {}", e);
+                    self.rpc_watchdog.record_error("eth_chainId", &e.clone().into());
+                }
+                Ok(check.outcome.get_or_init(|| outcome).clone()?)
+            }
+            Err(e) => {
+                // Try again on the next call
+                println!("⚠️ Failed to verify chain id: {}", e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Run an RPC future to completion
+    fn block_on_rpc<T, E, F>(&self, method: &str, future: F) -> Result<T>
+    where
+        F: Future<Output = std::result::Result<T, E>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.run_rpc(method, future)?.map_err(|e| self.log_error(method, e.into()))
+    }
+
+    /// Record a failed call in the error log on its way out
+    pub(crate) fn log_error(&self, method: &str, error: eyre::Report) -> eyre::Report {
+        let error = self.classify_rate_limit(method, error);
+        self.rpc_watchdog.record_error(method, &error);
+        error
+    }
+
+    /// Run a view call at the configured block tag, falling back to `latest` when the node doesn't support it
+    fn with_read_block<T>(&self, call: impl Fn(Option<BlockId>) -> Result<T>) -> Result<T> {
+        let block = match self.read_block_tag {
+            ReadBlockTag::Latest => None,
+            _ if self.read_block_tag_unsupported.load(Ordering::SeqCst) => None,
+            tag => Some(BlockId::Number(tag.into())),
+        };
+        match call(block) {
+            Err(e) if block.is_some() && is_unsupported_block_tag(&e) => {
+                println!("⚠️ Node does not support the {:?} block tag, falling back to latest: {}", self.read_block_tag, e);
+                self.read_block_tag_unsupported.store(true, Ordering::SeqCst);
+                call(None)
+            }
+            result => result,
+        }
+    }
+
+    /// Run a contract call to completion, decoding reverts into `StylusError::ContractRevert`
+    fn block_on_contract<T, M, F>(&self, method: &str, future: F) -> Result<T>
+    where
+        F: Future<Output = std::result::Result<T, ContractError<M>>>,
+        M: Middleware + 'static,
+    {
+        self.run_rpc(method, future)?.map_err(|e| self.decode_contract_error(method, e))
+    }
+
+    fn decode_contract_error<M: Middleware + 'static>(&self, method: &str, error: ContractError<M>) -> eyre::Report {
+        let error = match error.as_revert() {
+            Some(data) => StylusError::from_revert_data(self.abi(), data.clone()).into(),
+            None => error.into(),
+        };
+        self.log_error(method, error)
+    }
+
+    /// Track a submitted transaction until it is mined
+    fn track_pending(&self, hash: TxHash, function: &str, depth: usize) {
+        self.pending_transactions.track(hash, function, depth);
+        self.event_stream.publish(StylusEvent::Submitted { hash, function: function.to_string() });
+        if let Some(provider) = &self.provider {
+            self.pending_transactions.watch(hash, provider.clone(), depth, self.runtime.clone());
+        }
+    }
+
+    /// Whether the client can only perform view calls (no signer available)
+    pub fn is_read_only(&self) -> bool {
+        self.read_only_reason.is_some()
+    }
+
     /// Convert a u8 to U256 for blockchain operations
     pub fn u8_to_u256(&self, value: u8) -> U256 {
         U256::from(value)
@@ -78,16 +745,33 @@ impl StylusClient {
         U256::from(value)
     }
 
-    /// Get sword counts from the blockchain
+    /// Convert a U256 from the contract to u64, failing instead of truncating when it doesn't fit
+    pub fn u256_to_u64(&self, value: U256) -> Result<u64> {
+        u64::try_from(value).map_err(|_| eyre::eyre!("{} does not fit in a u64", value))
+    }
+
+    /// Get sword counts from the blockchain.
+    ///
+    /// Fails if a count doesn't fit in a u64; use
+    /// [`get_sword_counts_raw`](Self::get_sword_counts_raw) for the full values.
     pub fn get_sword_counts(&self) -> Result<(u64, u64, u64)> {
-        if let Some(contract) = &self.contract {
-            let runtime = tokio::runtime::Runtime::new()?;
-            let result = runtime.block_on(contract.get_sword_counts().call())?;
-            Ok((
-                result.0.as_u64(),
-                result.1.as_u64(),
-                result.2.as_u64(),
-            ))
+        let (a, b, c) = self.get_sword_counts_raw()?;
+        Ok((self.u256_to_u64(a)?, self.u256_to_u64(b)?, self.u256_to_u64(c)?))
+    }
+
+    /// Get sword counts from the blockchain as returned by the contract, without narrowing
+    pub fn get_sword_counts_raw(&self) -> Result<(U256, U256, U256)> {
+        if let Some(contract) = &self.read_contract {
+            self.call_contract(contract.get_sword_counts())
+        } else {
+            Err(eyre::eyre!("Contract not initialized"))
+        }
+    }
+
+    /// Whether `user` is currently allowed to increment a sword
+    pub fn can_increment(&self, user: Address) -> Result<bool> {
+        if let Some(contract) = &self.read_contract {
+            self.call_contract(contract.can_increment(user))
         } else {
             Err(eyre::eyre!("Contract not initialized"))
         }
@@ -95,116 +779,831 @@ impl StylusClient {
 
     /// Increment sword count on the blockchain
     pub fn increment_sword(&self, color: u8) -> Result<()> {
+        if let Some(reason) = &self.read_only_reason {
+            return Err(eyre::eyre!("Client is read-only: {}", reason));
+        }
         if let Some(contract) = &self.contract {
-            let runtime = tokio::runtime::Runtime::new()?;
-            let _ = runtime.block_on(contract.increment_sword(self.u8_to_u256(color)).send())?;
+            self.send_contract(contract.increment_sword(self.u8_to_u256(color)))?;
             Ok(())
         } else {
             Err(eyre::eyre!("Contract not initialized"))
         }
     }
 
-    /// Increment sword count on the blockchain asynchronously (spawns a thread)
-    pub fn increment_sword_async(&self, color: u8) {
-        if let Some(contract) = &self.contract {
-            let contract = contract.clone();
-            let color_u256 = self.u8_to_u256(color);
-            std::thread::spawn(move || {
-                tokio::runtime::Runtime::new().unwrap().block_on(async {
-                    let _ = contract.increment_sword(color_u256).send().await;
-                });
-            });
+    /// The raw `incrementSword` builder, for tweaks the wrappers don't cover (access list,
+    /// gas, block, ...).
+    ///
+    /// The builder holds a clone of the client's signer middleware, so it signs with the
+    /// client's wallet and uses its provider, and it stays valid after the client is
+    /// replaced, e.g. by `switch_network`. Submit it with
+    /// [`send_contract`](Self::send_contract) to keep dedup, history and pending tracking;
+    /// calling `.send()` directly bypasses them.
+    pub fn increment_sword_call(&self, color: u8) -> Result<ContractCall<StylusSigner, ()>> {
+        let contract = self.contract.as_ref().ok_or(StylusError::NotInitialized)?;
+        Ok(contract.increment_sword(self.u8_to_u256(color)))
+    }
+
+    /// Increment sword count unless it would cost more than `max_wei`.
+    ///
+    /// Gas and gas price are estimated before sending and the write is refused with
+    /// `StylusError::CostExceedsMax` when `gas * gas_price` is above the cap. The
+    /// estimate is an upper bound, so the actual cost is usually slightly lower.
+    pub fn increment_sword_max_spend(&self, color: u8, max_wei: U256) -> Result<TxHash> {
+        let contract = self.contract.as_ref().ok_or(StylusError::NotInitialized)?;
+        let call = contract.increment_sword(self.u8_to_u256(color));
+        let options = contract::SendOptions { max_spend: Some(max_wei), ..Default::default() };
+        self.send_transaction_with("incrementSword", call.tx, options)
+    }
+
+    /// Increment sword count, signing with `wallet` instead of the configured key.
+    ///
+    /// This is a per-call override: the client's default wallet is left unchanged.
+    /// Works in read-only mode too, as long as the provider is available.
+    pub fn increment_sword_with_signer(&self, color: u8, wallet: LocalWallet) -> Result<TxHash> {
+        let provider = self.write_provider.as_ref().ok_or(StylusError::NotInitialized)?;
+        let address = self.contract_address.ok_or(StylusError::NotInitialized)?;
+        let chain_id = match &self.contract_client {
+            Some(signer) => signer.signer().chain_id(),
+            None => StylusError::check_chain_id(self.block_on_rpc("eth_chainId", provider.get_chainid())?.as_u64(), None)?,
+        };
+        let signer = Arc::new(SignerMiddleware::new((**provider).clone(), wallet.with_chain_id(chain_id)));
+        let call = BlockchainContract::new(address, signer).increment_sword(self.u8_to_u256(color));
+        let tx_hash = self.block_on_contract("incrementSword", async { call.send().await.map(|pending| pending.tx_hash()) })?;
+        self.track_pending(tx_hash, "incrementSword", self.confirmation_policy.depth_for(U256::zero()));
+        Ok(tx_hash)
+    }
+
+    /// Increment sword count on the blockchain asynchronously (spawns a thread).
+    ///
+    /// The outcome is reported through `TxConfirmed` / `TxFailed`, or can be waited
+    /// on with [`await_async`](Self::await_async). With `simulate_async_writes`
+    /// enabled, a revert is detected by an `eth_call` before sending and reported
+    /// as `TxFailed` without a hash.
+    pub fn increment_sword_async(&self, color: u8) -> AsyncWriteId {
+        let id = self.pending_transactions.start_async();
+        let Some(contract) = &self.contract else {
+            self.pending_transactions.async_rejected(id, "Contract not initialized".to_string());
+            return id;
+        };
+        let call = contract.increment_sword(self.u8_to_u256(color));
+        let client = self.clone();
+        std::thread::spawn(move || {
+            // Nobody joins this thread, so a panic would vanish and leave `id` submitting
+            // forever; report it as a rejected write instead
+            let submitted = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                if client.simulate_async_writes {
+                    client.block_on_contract("incrementSword", call.call())?;
+                }
+                client.send_contract(call)
+            }));
+            match submitted {
+                Ok(Ok(tx_hash)) => client.pending_transactions.async_sent(id, tx_hash),
+                Ok(Err(e)) => client.pending_transactions.async_rejected(id, format!("{:#}", e)),
+                Err(panic) => {
+                    let reason = format!("incrementSword panicked: {}", panic_message(&*panic));
+                    error!("{}", reason);
+                    client.pending_transactions.async_rejected(id, reason);
+                }
+            }
+        });
+        id
+    }
+
+    /// Increment sword count, wait for the transaction to be mined and confirmed, then
+    /// read the new counts.
+    ///
+    /// The blocking waits run on a dedicated thread, and the task on the IO pool only
+    /// awaits its result; poll it from a system, e.g. with
+    /// `block_on(future::poll_once(&mut task))`. Errors say whether sending, confirming
+    /// (within two minutes) or reading failed.
+    pub fn increment_and_refresh(&self, color: u8) -> Task<Result<SwordCounts>> {
+        let client = self.clone();
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(client.increment_and_read(color));
+        });
+        IoTaskPool::get().spawn(async move {
+            receiver
+                .await
+                .unwrap_or_else(|_| Err(eyre::eyre!("increment_and_refresh: background thread panicked")))
+        })
+    }
+
+    /// Read a raw 32-byte storage slot of the contract at the latest block
+    pub fn get_storage_at(&self, slot: U256) -> Result<H256> {
+        self.get_storage_at_block(slot, None)
+    }
+
+    /// Read a raw 32-byte storage slot of the contract, optionally at a historical block
+    pub fn get_storage_at_block(&self, slot: U256, block: Option<BlockId>) -> Result<H256> {
+        if let (Some(provider), Some(address)) = (&self.provider, self.contract_address) {
+            let mut location = [0u8; 32];
+            slot.to_big_endian(&mut location);
+            let value = self.block_on_rpc(
+                "eth_getStorageAt",
+                provider.get_storage_at(address, H256::from(location), block),
+            )?;
+            Ok(value)
+        } else {
+            Err(eyre::eyre!("Contract not initialized"))
         }
     }
+
+    /// Build a Multicall3 batcher for view calls on the configured network.
+    ///
+    /// Returns `Ok(None)` when no multicall contract is deployed at the configured
+    /// address, so callers can degrade to sequential calls.
+    pub fn multicall(&self) -> Result<Option<Multicall<Provider<StylusHttp>>>> {
+        if let (Some(provider), Some(address)) = (&self.provider, self.multicall_address) {
+            if !self.is_contract(address)? {
+                println!("⚠️ No multicall contract at {:?}, falling back to sequential calls", address);
+                return Ok(None);
+            }
+            let multicall = self.block_on_rpc("multicall", Multicall::new(provider.clone(), Some(address)))?;
+            Ok(Some(multicall))
+        } else {
+            Err(eyre::eyre!("Contract not initialized"))
+        }
+    }
+
+    /// Whether `address` has deployed code, i.e. is a contract rather than an EOA or empty account
+    pub fn is_contract(&self, address: Address) -> Result<bool> {
+        if let Some(provider) = &self.provider {
+            let code = self.block_on_rpc("eth_getCode", provider.get_code(address, None))?;
+            Ok(!code.is_empty())
+        } else {
+            Err(eyre::eyre!("Contract not initialized"))
+        }
+    }
+
+    /// Block until the contract is activated and answers a probe call.
+    ///
+    /// Waits for `activation_tx_hash` to be mined (when configured), then for
+    /// `getSwordCounts` to succeed. Fails with `StylusError::Timeout` if that doesn't
+    /// happen within `timeout`, or right away if the activation transaction reverted.
+    pub fn wait_for_activation(&self, timeout: Duration) -> Result<()> {
+        let provider = self
+            .provider
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Contract not initialized"))?;
+        let started = Instant::now();
+        let mut pending_activation = self.activation_tx_hash;
+        loop {
+            if let Some(hash) = pending_activation {
+                match self.block_on_rpc("eth_getTransactionReceipt", provider.get_transaction_receipt(hash))? {
+                    Some(receipt) if receipt.status == Some(1.into()) => pending_activation = None,
+                    Some(_) => return Err(eyre::eyre!("Activation transaction {:?} reverted", hash)),
+                    None => {}
+                }
+            }
+            if pending_activation.is_none() && self.get_sword_counts().is_ok() {
+                return Ok(());
+            }
+            if started.elapsed() >= timeout {
+                return Err(StylusError::Timeout { method: "wait_for_activation".to_string(), timeout }.into());
+            }
+            std::thread::sleep(self.confirmation_poll_interval.min(timeout.saturating_sub(started.elapsed())));
+        }
+    }
+
+    /// Block until the chain head reaches block `target`, polling on the confirmation poll interval.
+    ///
+    /// Fails with `StylusError::BlockTimeout`, carrying the last block seen, if that
+    /// doesn't happen within `timeout`.
+    pub fn wait_for_block(&self, target: u64, timeout: Duration) -> Result<()> {
+        let provider = self.provider.as_ref().ok_or(StylusError::NotInitialized)?;
+        let started = Instant::now();
+        loop {
+            let head = self.block_on_rpc("eth_blockNumber", provider.get_block_number())?.as_u64();
+            if head >= target {
+                return Ok(());
+            }
+            if started.elapsed() >= timeout {
+                return Err(StylusError::BlockTimeout { target, last_seen: head, timeout }.into());
+            }
+            std::thread::sleep(self.confirmation_poll_interval.min(timeout.saturating_sub(started.elapsed())));
+        }
+    }
+
+    /// Query the node's client and network version
+    pub fn node_info(&self) -> Result<NodeInfo> {
+        let provider = self.provider.as_ref().ok_or(StylusError::NotInitialized)?;
+        Ok(NodeInfo {
+            client_version: self.block_on_rpc("web3_clientVersion", provider.client_version()).ok(),
+            net_version: self.block_on_rpc("net_version", provider.get_net_version()).ok(),
+        })
+    }
+
+    /// Fetch a full transaction (nonce, gas, input, ...) by hash, `None` if unknown
+    pub fn get_transaction(&self, hash: TxHash) -> Result<Option<Transaction>> {
+        if let Some(provider) = &self.provider {
+            let transaction = self.block_on_rpc("eth_getTransactionByHash", provider.get_transaction(hash))?;
+            Ok(transaction)
+        } else {
+            Err(eyre::eyre!("Contract not initialized"))
+        }
+    }
+}
+
+/// Connects to the contract described by `Stylus.toml`, or by an embedded config.
+///
+/// An embedded config replaces `Stylus.toml` entirely: the file is then neither
+/// read nor merged in.
+#[derive(Default)]
+pub struct StylusPlugin {
+    /// Embedded TOML configuration, see [`from_config_str`](Self::from_config_str)
+    pub config: Option<Vec<u8>>,
+    /// Tokio runtime of the app to run RPC calls on, instead of the plugin's own.
+    ///
+    /// It must be a multi-threaded runtime with IO and time enabled, and must outlive
+    /// every use of the client, including background confirmation and polling threads.
+    pub runtime: Option<tokio::runtime::Handle>,
+    /// Game event decoders added with [`on_event`](Self::on_event)
+    pub log_decoders: Vec<LogDecoderHook>,
+}
+
+impl StylusPlugin {
+    /// Use `toml` as the configuration, e.g. `include_str!("../Stylus.toml")`
+    pub fn from_config_str(toml: &str) -> Self {
+        Self::from_config_bytes(toml.as_bytes())
+    }
+
+    /// Use UTF-8 encoded TOML as the configuration, e.g. `include_bytes!("../Stylus.toml")`
+    pub fn from_config_bytes(toml: &[u8]) -> Self {
+        Self { config: Some(toml.to_vec()), ..Default::default() }
+    }
+
+    /// Emit `E` for every polled contract log `decoder` recognizes, e.g. to map contract
+    /// events onto the game's own event types. `SwordIncremented` is registered this way.
+    ///
+    /// Logs come from the event poller, so `event_poll_interval_ms` must be set and
+    /// `event_filter` applies. `decoder` is called on the main thread in `Update`, once
+    /// per log in block order and in the frame the poll finishes, so it should be cheap and pure.
+    /// Return `None` for logs that aren't `E`. Logs removed by a reorg are not retracted.
+    /// Registering `E` again replaces its decoder, so each log still yields at most one `E`.
+    pub fn on_event<E: Event>(mut self, decoder: fn(&Log) -> Option<E>) -> Self {
+        self.log_decoders.push(LogDecoderHook::new(decoder));
+        self
+    }
 }
 
-pub struct StylusPlugin;
+/// Configuration embedded through `StylusPlugin::from_config_*`, read by `init_stylus`
+#[derive(Resource, Clone)]
+pub struct EmbeddedConfig(Vec<u8>);
 
 impl Plugin for StylusPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, init_stylus);
+        if let Some(config) = &self.config {
+            app.insert_resource(EmbeddedConfig(config.clone()));
+        }
+        app.insert_resource(StylusRuntime::new(self.runtime.clone()));
+        (LogDecoderHook::new(SwordIncremented::decode_log).0)(app);
+        for hook in &self.log_decoders {
+            (hook.0)(app);
+        }
+        app.add_event::<StylusReadOnlyFallback>()
+            .add_event::<StylusReady>()
+            .add_event::<NetworkMismatch>()
+            .add_event::<NonceResynced>()
+            .add_event::<SlowRpc>()
+            .add_event::<TxConfirmed>()
+            .add_event::<TxFailed>()
+            .add_event::<TxCancelled>()
+            .add_event::<ContractLog>()
+            .add_event::<BalanceChanged>()
+            .add_event::<NetworkSwitched>()
+            .add_event::<RetryTx>()
+            .add_event::<TxGasRetried>()
+            .add_event::<IncrementAndRefresh>()
+            .add_event::<IncrementRefreshed>()
+            .init_resource::<StylusMetrics>()
+            .init_resource::<StylusErrorLog>()
+            .init_resource::<events::LogPoller>()
+            .init_resource::<balance::BalancePoller>()
+            .init_resource::<SwordIncrementQueue>()
+            .init_resource::<SequentialWriteQueue>()
+            .init_resource::<SessionSpend>()
+            .init_resource::<GasStats>()
+            .init_resource::<refresh::IncrementRefreshes>()
+            .add_systems(Startup, init_stylus)
+            .add_systems(Last, (queue::submit_sword_increments, sequential::drive_sequential_writes).chain())
+            .add_systems(
+                Update,
+                (
+                    drain_rpc_watchdog,
+                    drain_pending_transactions,
+                    events::poll_contract_events,
+                    balance::poll_signer_balance,
+                    network::drain_network_switches,
+                    history::retry_failed_transactions,
+                    spend::track_session_spend,
+                    spend::track_gas_stats,
+                    refresh::drive_increment_refreshes,
+                ),
+            )
+            .register_stylus_read::<SwordCounts>(None);
     }
 }
 
-pub fn init_stylus(mut commands: Commands) {
-    let stylus_client = std::thread::spawn(|| {
-        tokio::runtime::Runtime::new()
-            .unwrap()
+pub fn init_stylus(
+    mut commands: Commands,
+    mut fallback_events: EventWriter<StylusReadOnlyFallback>,
+    mut ready_events: EventWriter<StylusReady>,
+    mut mismatch_events: EventWriter<NetworkMismatch>,
+    embedded: Option<Res<EmbeddedConfig>>,
+    runtime: Option<Res<StylusRuntime>>,
+) {
+    let embedded = embedded.map(|embedded| embedded.0.clone());
+    let runtime = runtime.map(|runtime| runtime.clone()).unwrap_or_default();
+    let stylus_client = std::thread::spawn(move || {
+        runtime
             .block_on(async {
-                init_stylus_client().await
+                let config = load_stylus_config(embedded.as_deref())?;
+                // Bound the whole init, an unreachable RPC host would otherwise stall the launch
+                let timeout = Duration::from_millis(config.options.init_timeout_ms);
+                tokio::time::timeout(timeout, init_stylus_client(config, runtime.clone()))
+                    .await
+                    .map_err(|_| StylusError::Timeout { method: "init_stylus_client".to_string(), timeout })?
             })
+            .and_then(|result| result)
     })
     .join()
-    .unwrap();
+    .unwrap_or_else(|panic| Err(eyre::eyre!("Stylus init panicked: {}", panic_message(&*panic))));
 
     match stylus_client {
         Ok(client) => {
-            println!("✅ Stylus client initialized successfully");
+            if let Some(reason) = &client.read_only_reason {
+                fallback_events.send(StylusReadOnlyFallback { reason: reason.clone() });
+            }
+            if let Some(mismatch) = &client.network_mismatch {
+                mismatch_events.send(mismatch.clone());
+            }
+            commands.insert_resource(StylusApiResource(Box::new(client.clone())));
             commands.insert_resource(client);
+            ready_events.send(StylusReady { error: None });
         }
         Err(e) => {
             println!("❌ Failed to initialize Stylus client: {:?}", e);
-            commands.insert_resource(StylusClient {
-                contract_client: None,
-                contract_address: None,
-                contract: None,
-            });
+            commands.insert_resource(StylusApiResource(Box::new(StylusClient::default())));
+            commands.insert_resource(StylusClient::default());
+            ready_events.send(StylusReady { error: Some(e.to_string()) });
         }
     }
 }
 
-async fn init_stylus_client() -> Result<StylusClient> {
+/// Message of a panic payload caught from a background thread
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Load the embedded config if there is one, `Stylus.toml` otherwise
+fn load_stylus_config(embedded: Option<&[u8]>) -> Result<StylusConfig> {
     dotenv().ok();
 
-    let mut client = StylusClient {
-        contract_client: None,
-        contract_address: None,
-        contract: None,
+    match embedded {
+        Some(bytes) => {
+            let config_content = std::str::from_utf8(bytes)
+                .map_err(|e| eyre::eyre!("Embedded Stylus config is not valid UTF-8: {}", e))?;
+            parse_stylus_config(config_content, "embedded Stylus config")
+        }
+        None => {
+            // Read Stylus.toml configuration
+            let config_content = fs::read_to_string("Stylus.toml")
+                .map_err(|e| eyre::eyre!("Failed to read Stylus.toml: {}", e))?;
+            parse_stylus_config(&config_content, "Stylus.toml")
+        }
+    }
+}
+
+fn parse_stylus_config(content: &str, source: &str) -> Result<StylusConfig> {
+    let missing = |path: &str| StylusError::MissingConfigField { source: source.to_string(), path: path.to_string() };
+    // Name the missing entry up front; serde's "missing field" doesn't say which section
+    if let Ok(Some(path)) = strict::missing_section_field(content) {
+        return Err(missing(&path).into());
+    }
+    let config: StylusConfig = toml::from_str(content).map_err(|e| eyre::eyre!("Failed to parse {}: {}", source, e))?;
+    let contract = networks::resolve_active(config.contract.clone(), config.active_network.as_deref(), &config.networks)?;
+    if contract.address.is_empty() {
+        return Err(missing("contract.address").into());
+    }
+    if contract.read_endpoint().is_empty() {
+        return Err(missing("contract.rpc_url").into());
+    }
+    if config.options.strict_config {
+        let unknown = strict::unknown_keys(content).map_err(|e| eyre::eyre!("Failed to parse {}: {}", source, e))?;
+        if !unknown.is_empty() {
+            return Err(eyre::eyre!("Unknown keys in {}: {}", source, unknown.join(", ")));
+        }
+    }
+    Ok(config)
+}
+
+async fn init_stylus_client(mut config: StylusConfig, runtime: StylusRuntime) -> Result<StylusClient> {
+    let mut client = StylusClient { runtime, ..Default::default() };
+    client.network_configs = networks::network_configs(&config.contract, &config.networks);
+    config.contract = networks::resolve_active(config.contract, config.active_network.as_deref(), &config.networks)?;
+
+    let banner = config.options.startup_banner;
+    info!(
+        address = %config.contract.address,
+        network = %config.contract.network,
+        read_rpc_url = %config.contract.read_endpoint(),
+        write_rpc_url = %config.contract.write_endpoint(),
+        functions = config.functions.signatures.len(),
+        "Loaded Stylus configuration"
+    );
+    if banner {
+        println!("📋 Loaded Stylus configuration:");
+        println!("  - Contract Address: {}", config.contract.address);
+        println!("  - Network: {}", config.contract.network);
+        if config.contract.read_endpoint() == config.contract.write_endpoint() {
+            println!("  - RPC URL: {}", config.contract.read_endpoint());
+        } else {
+            println!("  - Read RPC URL: {}", config.contract.read_endpoint());
+            println!("  - Write RPC URL: {}", config.contract.write_endpoint());
+        }
+        println!("  - Functions: {} signatures", config.functions.signatures.len());
+    }
+
+    // Get private key from the selected profile, the environment or use default
+    let profile = std::env::var("STYLUS_PROFILE").ok().or_else(|| config.options.key_profile.clone());
+    let private_key = match &profile {
+        Some(profile) => load_profile_key(&config.options.keys_file, profile)?,
+        None => std::env::var("PRIVATE_KEY")
+            .unwrap_or_else(|_| "0xb6b15c8cb491557369f3c7d2c287b053eb229daa9c22138887752191c9520659".to_string()),
     };
 
-    // Read Stylus.toml configuration
-    let config_content = fs::read_to_string("Stylus.toml")
-        .map_err(|e| eyre::eyre!("Failed to read Stylus.toml: {}", e))?;
-    
-    let config: StylusConfig = toml::from_str(&config_content)
-        .map_err(|e| eyre::eyre!("Failed to parse Stylus.toml: {}", e))?;
-
-    println!("📋 Loaded Stylus configuration:");
-    println!("  - Contract Address: {}", config.contract.address);
-    println!("  - Network: {}", config.contract.network);
-    println!("  - RPC URL: {}", config.contract.rpc_url);
-    println!("  - Functions: {} signatures", config.functions.signatures.len());
-
-    // Get private key from environment or use default
-    let private_key = std::env::var("PRIVATE_KEY")
-        .unwrap_or_else(|_| "0xb6b15c8cb491557369f3c7d2c287b053eb229daa9c22138887752191c9520659".to_string());
-
-    println!("🔑 Using private key: {}", if private_key.len() > 10 { 
-        format!("{}...{}", &private_key[..10], &private_key[private_key.len()-10..]) 
-    } else { 
-        private_key.clone() 
-    });
-
-    // Create provider and wallet
-    let provider = Provider::<Http>::try_from(&config.contract.rpc_url)?;
-    let wallet = LocalWallet::from_str(&private_key)?;
-    let chain_id = provider.get_chainid().await?.as_u64();
-    let client_arc = Arc::new(SignerMiddleware::new(
-        provider,
-        wallet.with_chain_id(chain_id),
-    ));
+    if let Some(profile) = &profile {
+        info!(profile = %profile, "Using key profile");
+        if banner {
+            println!("🔑 Using key profile: {}", profile);
+        }
+    } else if banner {
+        println!("🔑 Using private key: {}", if private_key.len() > 10 { 
+            format!("{}...{}", &private_key[..10], &private_key[private_key.len()-10..]) 
+        } else { 
+            private_key.clone() 
+        });
+    }
 
+    // Create provider and read-only contract binding
+    client.confirmation_poll_interval = Duration::from_millis(config.options.confirmation_poll_interval_ms);
+    client.http_client = config.options.http_client()?;
+    client.rate_limit_retry = transport::RateLimitRetry {
+        max_retries: config.options.rate_limit_retries,
+        max_wait: Duration::from_millis(config.options.rate_limit_max_wait_ms),
+    };
+    let (provider, write_provider) = config.contract.providers(&client)?;
     let contract_address: Address = config.contract.address.parse()?;
-    let contract = BlockchainContract::new(contract_address, client_arc.clone());
 
-    client.contract_client = Some(client_arc);
+    client.provider = Some(provider.clone());
+    client.write_provider = Some(write_provider.clone());
+    client.read_poll_interval = config.options.read_poll_interval_ms.map(Duration::from_millis);
+    client.event_poll_interval = config.options.event_poll_interval_ms.map(Duration::from_millis);
+    client.balance_poll_interval = config.options.balance_poll_interval_ms.map(Duration::from_millis);
+    client.event_filter = config.options.event_filter;
+    client.read_block_tag = config.options.read_block_tag;
+    client.simulate_async_writes = config.options.simulate_async_writes;
+    client.thousands_separator = config.options.thousands_separator;
+    client.check_gas_funds = config.options.check_gas_funds;
+    client.coalesce_increments = config.options.coalesce_increments;
+    client.reset_session_spend_on_switch = config.options.reset_session_spend_on_switch;
+    client.gas_stats_window = config.options.gas_stats_window;
+    client.event_stream = stream::EventStream::new(config.options.event_channel_capacity);
+    client.sequential_writes = config.options.sequential_writes;
+    client.sequential_await_receipt = config.options.sequential_await_receipt;
+    client.auto_retry_out_of_gas = config.options.auto_retry_out_of_gas;
+    client.out_of_gas_retry_bump_percent = config.options.out_of_gas_retry_bump_percent;
+    client.confirmation_policy = ConfirmationPolicy {
+        thresholds: config
+            .options
+            .confirmation_thresholds
+            .iter()
+            .map(|threshold| {
+                let min_value = parse_ether(&threshold.min_value_ether)
+                    .map_err(|e| eyre::eyre!("Invalid min_value_ether {}: {}", threshold.min_value_ether, e))?;
+                Ok((min_value, threshold.depth))
+            })
+            .collect::<Result<_>>()?,
+    };
+    client.write_dedup = WriteDedup::new(config.options.dedup_window_ms.map(Duration::from_millis));
+    client.gas_price_premium_percent = config.options.gas_price_premium_percent;
+    if client.gas_price_premium_percent > contract::MAX_GAS_PRICE_PREMIUM_PERCENT {
+        println!(
+            "⚠️ gas_price_premium_percent {} is above the maximum, using {}",
+            client.gas_price_premium_percent,
+            contract::MAX_GAS_PRICE_PREMIUM_PERCENT
+        );
+        client.gas_price_premium_percent = contract::MAX_GAS_PRICE_PREMIUM_PERCENT;
+    }
+    client.rpc_watchdog = RpcWatchdog::new(
+        config.options.slow_rpc_threshold_ms.map(Duration::from_millis),
+        config.options.slow_rpc_action,
+    );
+    client.rpc_watchdog.error_log_size = config.options.error_log_size;
+    if let Some(path) = &config.options.pending_tx_file {
+        client.pending_transactions = PendingTransactions::with_persistence(path);
+        // Re-poll transactions submitted before the last shutdown
+        for tx in client.pending_transactions.all() {
+            client.pending_transactions.watch(tx.hash, provider.clone(), tx.depth, client.runtime.clone());
+        }
+    }
+    if let Some(chain_id) = config.contract.chain_id {
+        client.chain_id_check = Some(network::ChainIdCheck::new(chain_id));
+    }
+    client.multicall_address = Some(config.contract.multicall_address()?);
+    #[cfg(feature = "erc20")]
+    {
+        client.token_address = config.contract.token_address()?;
+    }
     client.contract_address = Some(contract_address);
-    client.contract = Some(contract);
+    client.function_signatures = config.functions.signatures.clone();
+    client.explorer_base_url = config.contract.explorer_base_url.clone();
+    client.private_tx_url = config.contract.private_tx_url.clone();
+    client.relayer = config.relayer.clone();
+    client.activation_tx_hash = match config.deployment.activation_tx_hash.parse() {
+        Ok(hash) => Some(hash),
+        Err(e) => {
+            println!("⚠️ Ignoring invalid activation_tx_hash {}: {}", config.deployment.activation_tx_hash, e);
+            None
+        }
+    };
+    client.read_contract = Some(BlockchainContract::new(contract_address, provider.clone()));
+
+    // Establish the connection before gameplay starts; failures are not fatal
+    if config.options.prewarm {
+        match provider.get_block_number().await {
+            Ok(block) => println!("🔥 Prewarmed RPC connection (block {})", block),
+            Err(e) => println!("⚠️ Failed to prewarm RPC connection: {}", e),
+        }
+        if !Arc::ptr_eq(&provider, &write_provider) {
+            match write_provider.get_block_number().await {
+                Ok(block) => println!("🔥 Prewarmed write RPC connection (block {})", block),
+                Err(e) => println!("⚠️ Failed to prewarm write RPC connection: {}", e),
+            }
+        }
+    }
+
+    // Catch "contract not deployed on this network" (e.g. after a testnet reset) early
+    if config.options.check_contract_code {
+        match provider.get_code(contract_address, None).await {
+            Ok(code) if code.is_empty() => println!(
+                "⚠️ No contract code at {:?} on {}, is the contract deployed on this network?",
+                contract_address, config.contract.network
+            ),
+            Ok(_) => {}
+            Err(e) => println!("⚠️ Failed to check contract code: {}", e),
+        }
+    }
+
+    // Catch ABI/contract drift, e.g. a renamed function or an outdated deployment
+    if config.options.validate_abi {
+        let missing = abi_check::missing_functions(&provider, contract_address, &config.functions.signatures).await;
+        for signature in &missing {
+            warn!(function = %signature, "Function seems to be missing from the contract");
+            println!("⚠️ {} seems to be missing from the contract at {:?}", signature, contract_address);
+        }
+        if banner && missing.is_empty() {
+            println!("🔎 Every ABI function answered its probe");
+        }
+    }
+
+    if config.contract.resolve_proxy {
+        match proxy::read_implementation(&provider, contract_address).await {
+            Ok(Some(implementation)) => {
+                println!("🪞 Contract {:?} is a proxy for {:?}", contract_address, implementation);
+                client.implementation_address = Some(implementation);
+            }
+            Ok(None) => println!("⚠️ resolve_proxy is set but {:?} has no EIP-1967 implementation", contract_address),
+            Err(e) => println!("⚠️ Failed to resolve proxy implementation: {}", e),
+        }
+    }
+
+    // Attach the signer, falling back to read-only mode if it can't be loaded
+    match LocalWallet::from_str(&private_key) {
+        Ok(wallet) => {
+            // A chain id of 0 (e.g. a misconfigured local node) gets every transaction rejected.
+            // A configured one is compared with the node's on the first call.
+            let chain_id = match config.contract.chain_id {
+                Some(chain_id) => StylusError::check_chain_id(chain_id, None)?,
+                None => network::node_chain_id(&*provider, None).await?,
+            };
+            let client_arc = Arc::new(SignerMiddleware::new(
+                (*write_provider).clone(),
+                wallet.with_chain_id(chain_id),
+            ));
+            let contract = BlockchainContract::new(contract_address, client_arc.clone());
+
+            client.chain_id = Some(chain_id);
+            client.contract_client = Some(client_arc);
+            client.contract = Some(contract);
+        }
+        Err(e) => {
+            println!("⚠️ Failed to load signer, falling back to read-only mode: {}", e);
+            client.read_only_reason = Some(format!("Failed to load signer: {}", e));
+            client.chain_id = match config.contract.chain_id {
+                Some(chain_id) => Some(StylusError::check_chain_id(chain_id, None)?),
+                None => match network::node_chain_id(&*provider, None).await {
+                    Ok(chain_id) => Some(chain_id),
+                    Err(e) => {
+                        println!("⚠️ Failed to fetch chain id: {}", e);
+                        None
+                    }
+                },
+            };
+        }
+    }
+
+    // A name/chain disagreement can mean real funds on the wrong network, so shout about it
+    client.declared_chain_ids = networks::declared_chain_ids(&config.networks);
+    if let Some(chain_id) = client.chain_id {
+        client.network_mismatch = networks::check_network(&config.contract.network, chain_id, &client.declared_chain_ids);
+        if let Some(mismatch) = &client.network_mismatch {
+            networks::report_mismatch(mismatch);
+        }
+    }
 
-    println!("✅ Stylus client initialized successfully!");
+    client.contract_config = Some(config.contract);
+    info!(read_only = client.is_read_only(), "Stylus client initialized");
+    if banner {
+        println!("✅ Stylus client initialized successfully!");
+    }
 
     Ok(client)
 }
 
 // Re-export the contract type for convenience
-pub use BlockchainContract;
\ No newline at end of file
+pub use BlockchainContract;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{call_data, encoded, MockNode};
+    use ethers::abi::Token;
+    use serde_json::Value;
+
+    #[test]
+    fn get_transaction_fetches_by_hash() {
+        let hash = TxHash::repeat_byte(0xab);
+        let node = MockNode::start(move |method, params| {
+            assert_eq!(method, "eth_getTransactionByHash");
+            Ok(match params[0].as_str() {
+                Some(requested) if requested == format!("{:?}", hash) => {
+                    serde_json::to_value(Transaction { hash, nonce: 7.into(), ..Default::default() }).unwrap()
+                }
+                _ => Value::Null,
+            })
+        });
+        let transaction = node.client().get_transaction(hash).unwrap().unwrap();
+        assert_eq!((transaction.hash, transaction.nonce), (hash, 7.into()));
+        assert!(node.client().get_transaction(TxHash::zero()).unwrap().is_none());
+    }
+
+    #[test]
+    fn is_contract_checks_for_code() {
+        let contract = Address::repeat_byte(1);
+        let node = MockNode::start(move |method, params| {
+            assert_eq!(method, "eth_getCode");
+            let has_code = params[0].as_str() == Some(format!("{:?}", contract).as_str());
+            Ok(Value::String(if has_code { "0x6080604052" } else { "0x" }.to_string()))
+        });
+        let client = node.client();
+        assert!(client.is_contract(contract).unwrap());
+        assert!(!client.is_contract(Address::repeat_byte(2)).unwrap());
+        assert!(StylusClient::default().is_contract(contract).is_err());
+    }
+
+    #[test]
+    fn can_increment_decodes_the_bool() {
+        let allowed = Address::repeat_byte(1);
+        let node = MockNode::start(move |_, params| {
+            let (selector, args) = call_data(params);
+            assert_eq!(selector, BLOCKCHAINCONTRACT_ABI.function("canIncrement").unwrap().short_signature());
+            Ok(encoded(&[Token::Bool(args == ethers::abi::encode(&[Token::Address(allowed)]))]))
+        });
+        let client = node.client();
+        assert!(client.can_increment(allowed).unwrap());
+        assert!(!client.can_increment(Address::repeat_byte(2)).unwrap());
+    }
+
+    #[test]
+    fn sword_counts_are_narrowed_without_truncating() {
+        let huge = U256::from(u64::MAX) + 1;
+        let node = MockNode::start(move |_, params| {
+            let (selector, _) = call_data(params);
+            assert_eq!(selector, BLOCKCHAINCONTRACT_ABI.function("getSwordCounts").unwrap().short_signature());
+            Ok(encoded(&[Token::Uint(1.into()), Token::Uint(huge), Token::Uint(3.into())]))
+        });
+        let client = node.client();
+        assert_eq!(client.get_sword_counts_raw().unwrap(), (1.into(), huge, 3.into()));
+        let error = client.get_sword_counts().unwrap_err();
+        assert_eq!(error.to_string(), "18446744073709551616 does not fit in a u64");
+    }
+
+    #[test]
+    fn u256_to_u64_is_checked() {
+        let client = StylusClient::default();
+        assert_eq!(client.u256_to_u64(U256::from(u64::MAX)).unwrap(), u64::MAX);
+        assert!(client.u256_to_u64(U256::MAX).is_err());
+    }
+
+    #[test]
+    fn view_call_reverts_name_the_custom_error() {
+        let selector = BLOCKCHAINCONTRACT_ABI.error("Unauthorized").unwrap().signature()[..4].to_vec();
+        let node = MockNode::start(move |_, _| {
+            Err(ethers::providers::JsonRpcError {
+                code: 3,
+                message: "execution reverted".to_string(),
+                data: Some(Value::String(format!("0x{}", ethers::utils::hex::encode(&selector)))),
+            })
+        });
+        let error = node.client().get_sword_counts().unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<StylusError>(),
+            Some(StylusError::ContractRevert { name: Some(name), .. }) if name == "Unauthorized"
+        ));
+    }
+
+    /// A node on chain `chain_id` answering `getSwordCounts` with (1, 2, 3), logging every method
+    fn counting_node(chain_id: u64) -> (MockNode, Arc<Mutex<Vec<String>>>) {
+        let methods = Arc::new(Mutex::new(Vec::new()));
+        let log = methods.clone();
+        let node = MockNode::start(move |method, _| {
+            log.lock().unwrap().push(method.to_string());
+            Ok(match method {
+                "eth_chainId" => Value::String(format!("{:#x}", chain_id)),
+                _ => encoded(&[Token::Uint(1.into()), Token::Uint(2.into()), Token::Uint(3.into())]),
+            })
+        });
+        (node, methods)
+    }
+
+    /// Init against `node` with `chain_id = 412346` and no startup probes
+    fn init_with_chain_id(node: &MockNode) -> StylusClient {
+        let content = format!(
+            r#"
+[contract]
+address = "0x00000000000000000000000000000000000000cc"
+network = "nitro-devnode"
+rpc_url = "{}"
+chain_id = 412346
+
+[deployment]
+tx_hash = "0x1"
+activation_tx_hash = "0x2"
+contract_size = "1 KB"
+wasm_size = "4 KB"
+wasm_data_fee = "0.0001 ETH"
+
+[functions]
+signatures = []
+
+[options]
+prewarm = false
+check_contract_code = false
+startup_banner = false
+"#,
+            node.url()
+        );
+        let config = parse_stylus_config(&content, "Stylus.toml").unwrap();
+        let runtime = StylusRuntime::default();
+        runtime.block_on(init_stylus_client(config, runtime.clone())).unwrap().unwrap()
+    }
+
+    #[test]
+    fn configured_chain_id_skips_startup_queries() {
+        let (node, methods) = counting_node(412346);
+        let client = init_with_chain_id(&node);
+        assert!(methods.lock().unwrap().is_empty());
+        assert_eq!(client.chain_id(), Some(412346));
+        assert!(!client.is_read_only());
+
+        // Checked once, on the first call
+        assert_eq!(client.get_sword_counts().unwrap(), (1, 2, 3));
+        assert_eq!(client.get_sword_counts().unwrap(), (1, 2, 3));
+        assert_eq!(*methods.lock().unwrap(), ["eth_chainId", "eth_call", "eth_call"]);
+    }
+
+    #[test]
+    fn configured_chain_id_mismatch_fails_every_call() {
+        let (node, methods) = counting_node(42161);
+        let client = init_with_chain_id(&node);
+        for _ in 0..2 {
+            let error = client.get_sword_counts().unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<StylusError>(),
+                Some(StylusError::InvalidChainId { chain_id: 42161, expected: Some(412346) })
+            ));
+        }
+        assert_eq!(*methods.lock().unwrap(), ["eth_chainId"]);
+    }
+
+    #[test]
+    fn get_transaction_needs_a_provider() {
+        assert!(StylusClient::default().get_transaction(TxHash::zero()).is_err());
+    }
+}
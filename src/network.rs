@@ -0,0 +1,185 @@
+use bevy::prelude::*;
+use ethers::prelude::{Middleware, Signer, SignerMiddleware};
+use ethers::types::Address;
+use eyre::Result;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::{
+    balance::BalancePoller, events::LogPoller, networks, proxy, BlockchainContract, ContractConfig, NetworkMismatch,
+    RefreshReads, StylusApiResource, StylusClient, StylusError,
+};
+
+/// Emitted once the client has been rebuilt by [`StylusClient::switch_network`]
+#[derive(Event, Debug, Clone)]
+pub struct NetworkSwitched {
+    pub network: String,
+    pub chain_id: u64,
+    pub contract_address: Address,
+}
+
+/// Switches waiting to be announced by `drain_network_switches`
+pub(crate) type NetworkSwitchQueue = Arc<Mutex<Vec<NetworkSwitched>>>;
+
+/// The chain id `provider` reports, failing when it is 0 or differs from `configured`
+pub(crate) async fn node_chain_id<M: Middleware>(provider: &M, configured: Option<u64>) -> Result<u64>
+where
+    M::Error: 'static,
+{
+    let chain_id = provider.get_chainid().await?.as_u64();
+    Ok(StylusError::check_chain_id(chain_id, configured)?)
+}
+
+/// A configured chain id that init trusts without asking the node, checked on the first call instead
+#[derive(Debug)]
+pub(crate) struct ChainIdCheck {
+    pub expected: u64,
+    /// The node's verdict, unset until `eth_chainId` succeeds
+    pub outcome: OnceLock<Result<(), StylusError>>,
+}
+
+impl ChainIdCheck {
+    pub(crate) fn new(expected: u64) -> Arc<Self> {
+        Arc::new(ChainIdCheck { expected, outcome: OnceLock::new() })
+    }
+}
+
+impl StylusClient {
+    /// Point the client at another network without restarting.
+    ///
+    /// Builds a new provider and contract bindings from `config`, re-attaching the
+    /// current wallet under the new chain id, then swaps them in place. Transactions
+    /// pending on the old network stop being tracked and no events are reported for
+    /// them, and the dedup window, write history and sequential nonce start over.
+    /// The `network` name is checked against the new chain as at init.
+    ///
+    /// Fails when the node's chain id differs from `config.chain_id`. On error the
+    /// client keeps using the old network.
+    pub fn switch_network(&mut self, config: ContractConfig) -> Result<()> {
+        let (provider, write_provider) = config.providers(self)?;
+        let contract_address: Address = config.address.parse()?;
+        let multicall_address = config.multicall_address()?;
+        #[cfg(feature = "erc20")]
+        let token_address = config.token_address()?;
+        let chain_id = self.runtime.block_on(node_chain_id(&*provider, config.chain_id))??;
+        let signer = self.contract_client.as_ref().map(|current| {
+            Arc::new(SignerMiddleware::new(
+                (*write_provider).clone(),
+                current.signer().clone().with_chain_id(chain_id),
+            ))
+        });
+
+        let implementation_address = if config.resolve_proxy {
+            self.runtime.block_on(proxy::read_implementation(&provider, contract_address))??
+        } else {
+            None
+        };
+
+        self.pending_transactions = self.pending_transactions.reset();
+        // Writes, nonces and history of the old network mean nothing on the new one
+        self.write_dedup.clear();
+        self.tx_history.clear();
+        if let Ok(mut nonce) = self.sequential_nonce.lock() {
+            *nonce = None;
+        }
+        self.network_mismatch = networks::check_network(&config.network, chain_id, &self.declared_chain_ids);
+        if let Some(mismatch) = &self.network_mismatch {
+            networks::report_mismatch(mismatch);
+        }
+        self.contract_config = Some(config.clone());
+        self.contract = signer
+            .as_ref()
+            .map(|signer| BlockchainContract::new(contract_address, signer.clone()));
+        self.contract_client = signer;
+        self.read_contract = Some(BlockchainContract::new(contract_address, provider.clone()));
+        self.provider = Some(provider);
+        self.write_provider = Some(write_provider);
+        self.contract_address = Some(contract_address);
+        self.multicall_address = Some(multicall_address);
+        #[cfg(feature = "erc20")]
+        {
+            self.token_address = token_address;
+        }
+        self.chain_id = Some(chain_id);
+        // Checked against the node above
+        self.chain_id_check = None;
+        self.explorer_base_url = config.explorer_base_url;
+        self.private_tx_url = config.private_tx_url;
+        self.implementation_address = implementation_address;
+        self.read_block_tag_unsupported = Arc::default();
+        self.sword_counts_cache = Default::default();
+
+        println!("🔀 Switched to {} (chain {}, contract {:?})", config.network, chain_id, contract_address);
+        if let Ok(mut switches) = self.network_switches.lock() {
+            switches.push(NetworkSwitched { network: config.network, chain_id, contract_address });
+        }
+        Ok(())
+    }
+}
+
+/// Announce network switches and drop state that belonged to the old network
+pub(crate) fn drain_network_switches(
+    mut commands: Commands,
+    client: Option<Res<StylusClient>>,
+    mut log_poller: ResMut<LogPoller>,
+    mut balance_poller: ResMut<BalancePoller>,
+    mut refresh: EventWriter<RefreshReads>,
+    mut switched: EventWriter<NetworkSwitched>,
+    mut mismatches: EventWriter<NetworkMismatch>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+    let switches = client
+        .network_switches
+        .lock()
+        .map(|mut switches| std::mem::take(&mut *switches))
+        .unwrap_or_default();
+    if switches.is_empty() {
+        return;
+    }
+
+    *log_poller = LogPoller::default();
+    *balance_poller = BalancePoller::default();
+    commands.insert_resource(StylusApiResource(Box::new(client.clone())));
+    refresh.send(RefreshReads);
+    switched.send_batch(switches);
+    if let Some(mismatch) = &client.network_mismatch {
+        mismatches.send(mismatch.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::Provider;
+    use ethers::types::U64;
+
+    #[tokio::test]
+    async fn node_chain_id_accepts_matching_config() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(U64::from(421614)).unwrap();
+        assert_eq!(node_chain_id(&provider, Some(421614)).await.unwrap(), 421614);
+    }
+
+    #[tokio::test]
+    async fn node_chain_id_rejects_mismatch() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(U64::from(42161)).unwrap();
+        let error = node_chain_id(&provider, Some(421614)).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<StylusError>(),
+            Some(StylusError::InvalidChainId { chain_id: 42161, expected: Some(421614) })
+        ));
+    }
+
+    #[tokio::test]
+    async fn node_chain_id_rejects_zero() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(U64::zero()).unwrap();
+        let error = node_chain_id(&provider, None).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<StylusError>(),
+            Some(StylusError::InvalidChainId { chain_id: 0, expected: None })
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+use eyre::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::{ContractConfig, StylusClient};
+
+/// Chain ids of common Arbitrum / Stylus networks, by normalized `network` name
+const KNOWN_NETWORKS: &[(&str, u64)] = &[
+    ("arbitrum", 42161),
+    ("arbitrum-one", 42161),
+    ("arbitrum-nova", 42170),
+    ("nova", 42170),
+    ("arbitrum-sepolia", 421614),
+    ("nitro-devnode", 412346),
+];
+
+/// A `[networks.<name>]` entry: the fields that differ per network. Everything else
+/// comes from the `[contract]` section.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct NetworkConfig {
+    address: Option<String>,
+    rpc_url: Option<String>,
+    chain_id: Option<u64>,
+}
+
+/// `base` with `name`'s entry applied on top
+fn overlay(base: &ContractConfig, name: &str, network: &NetworkConfig) -> ContractConfig {
+    let mut config = base.clone();
+    config.network = name.to_string();
+    if let Some(address) = &network.address {
+        config.address = address.clone();
+    }
+    if let Some(rpc_url) = &network.rpc_url {
+        config.rpc_url = rpc_url.clone();
+        // A shared endpoint from `[contract]` would point at the wrong chain
+        config.read_rpc_url = None;
+        config.write_rpc_url = None;
+    }
+    config.chain_id = network.chain_id.or(config.chain_id);
+    config
+}
+
+/// The `[contract]` config for every `[networks.<name>]` entry
+pub(crate) fn network_configs(base: &ContractConfig, networks: &HashMap<String, NetworkConfig>) -> HashMap<String, ContractConfig> {
+    networks
+        .iter()
+        .map(|(name, network)| (name.clone(), overlay(base, name, network)))
+        .collect()
+}
+
+/// Resolve `active_network`, failing with the configured names when it has no entry
+pub(crate) fn resolve_active(
+    base: ContractConfig,
+    active: Option<&str>,
+    networks: &HashMap<String, NetworkConfig>,
+) -> Result<ContractConfig> {
+    let Some(active) = active else {
+        return Ok(base);
+    };
+    match networks.get(active) {
+        Some(network) => Ok(overlay(&base, active, network)),
+        None => {
+            let mut names: Vec<&str> = networks.keys().map(String::as_str).collect();
+            names.sort_unstable();
+            Err(eyre::eyre!(
+                "active_network = \"{}\" has no [networks.{}] section (configured: {})",
+                active,
+                active,
+                if names.is_empty() { "none".to_string() } else { names.join(", ") }
+            ))
+        }
+    }
+}
+
+/// Chain ids declared in `[networks.<name>]` entries, for the mismatch check
+pub(crate) fn declared_chain_ids(networks: &HashMap<String, NetworkConfig>) -> HashMap<String, u64> {
+    networks
+        .iter()
+        .filter_map(|(name, network)| Some((name.clone(), network.chain_id?)))
+        .collect()
+}
+
+impl StylusClient {
+    /// Names of the `[networks.<name>]` entries in `Stylus.toml`
+    pub fn network_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.network_configs.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// [`switch_network`](Self::switch_network) to a `[networks.<name>]` entry from `Stylus.toml`
+    pub fn switch_to_network(&mut self, name: &str) -> Result<()> {
+        let config = self
+            .network_configs
+            .get(name)
+            .cloned()
+            .ok_or_else(|| eyre::eyre!("No [networks.{}] section in Stylus.toml", name))?;
+        self.switch_network(config)
+    }
+}
+
+/// Emitted after init or a network switch when the configured `network` name belongs to
+/// another chain than the one the node reports, e.g. `"arbitrum-one"` pointed at a testnet RPC
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct NetworkMismatch {
+    /// `network` from the `[contract]` section
+    pub network: String,
+    /// Chain id that name stands for
+    pub expected_chain_id: u64,
+    /// Chain id the client is connected to
+    pub chain_id: u64,
+}
+
+/// Log a mismatch found by [`check_network`] loudly, since it can mean real funds on the wrong network
+pub(crate) fn report_mismatch(mismatch: &NetworkMismatch) {
+    error!(
+        network = %mismatch.network,
+        expected_chain_id = mismatch.expected_chain_id,
+        chain_id = mismatch.chain_id,
+        "Configured network does not match the connected chain"
+    );
+    println!(
+        "🚨 network = \"{}\" is chain {}, but the client is connected to chain {}. Check rpc_url before sending anything!",
+        mismatch.network, mismatch.expected_chain_id, mismatch.chain_id
+    );
+}
+
+/// Lowercase with `_` and spaces as `-`, so `"Arbitrum One"` matches `arbitrum-one`
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase().replace(['_', ' '], "-")
+}
+
+/// Compare `network` against the chain ids declared under `[networks]`, then the built-in table.
+/// Unknown names are not checked.
+pub(crate) fn check_network(network: &str, chain_id: u64, extra: &HashMap<String, u64>) -> Option<NetworkMismatch> {
+    let name = normalize(network);
+    let expected_chain_id = extra
+        .iter()
+        .find(|(extra_name, _)| normalize(extra_name) == name)
+        .map(|(_, chain_id)| *chain_id)
+        .or_else(|| KNOWN_NETWORKS.iter().find(|(known, _)| *known == name).map(|(_, chain_id)| *chain_id))?;
+    (expected_chain_id != chain_id).then(|| NetworkMismatch { network: network.to_string(), expected_chain_id, chain_id })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_network_uses_builtin_names() {
+        let none = HashMap::new();
+        assert_eq!(check_network("Arbitrum One", 42161, &none), None);
+        assert_eq!(
+            check_network("arbitrum_one", 421614, &none),
+            Some(NetworkMismatch { network: "arbitrum_one".to_string(), expected_chain_id: 42161, chain_id: 421614 })
+        );
+        assert_eq!(check_network("my-devnet", 1337, &none), None);
+    }
+
+    #[test]
+    fn check_network_prefers_declared_chain_ids() {
+        let declared = HashMap::from([("Arbitrum-One".to_string(), 1337)]);
+        assert_eq!(check_network("arbitrum-one", 1337, &declared), None);
+        assert!(check_network("arbitrum-one", 42161, &declared).is_some());
+    }
+}
@@ -0,0 +1,501 @@
+use bevy::prelude::*;
+use ethers::prelude::{Middleware, PendingTransaction, Provider};
+use ethers::types::{TransactionReceipt, TxHash, U256, U64};
+use serde::{Deserialize, Serialize};
+use eyre::Result;
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{gas_retry::is_out_of_gas, DecodedEvent, StylusClient, StylusError, StylusEvent, StylusHttp, StylusRuntime, TxGasRetried, TxStatus};
+
+/// A submitted transaction that has not been mined yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTx {
+    pub hash: TxHash,
+    /// Contract function that produced the transaction
+    pub function: String,
+    /// Unix timestamp (seconds) of submission
+    pub submitted_at: u64,
+    /// Blocks to wait for on top of the including one before reporting confirmation
+    #[serde(default)]
+    pub depth: usize,
+    /// Stuck transaction this one was sent to replace, e.g. by `cancel_tx`
+    #[serde(default)]
+    pub replaces: Option<TxHash>,
+}
+
+/// Picks how deep a write must be buried before `TxConfirmed`, based on the ETH it sends
+#[derive(Debug, Clone, Default)]
+pub struct ConfirmationPolicy {
+    /// `(minimum value in wei, depth)` pairs; the deepest matching threshold wins
+    pub thresholds: Vec<(U256, usize)>,
+}
+
+impl ConfirmationPolicy {
+    /// Depth for a transaction sending `value` wei, 0 (just mined) if no threshold matches
+    pub fn depth_for(&self, value: U256) -> usize {
+        self.thresholds
+            .iter()
+            .filter(|(min_value, _)| value >= *min_value)
+            .map(|(_, depth)| *depth)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Emitted when a tracked transaction is mined successfully
+#[derive(Event, Debug, Clone)]
+pub struct TxConfirmed {
+    pub hash: TxHash,
+    pub receipt: TransactionReceipt,
+    /// Contract events emitted by the transaction
+    pub events: Vec<DecodedEvent>,
+}
+
+/// Emitted when a tracked transaction reverts, is dropped, or can't be polled,
+/// or when a fire-and-forget write is rejected before it is broadcast
+#[derive(Event, Debug, Clone)]
+pub struct TxFailed {
+    /// `None` when the write failed simulation or submission and was never broadcast
+    pub hash: Option<TxHash>,
+    pub reason: String,
+}
+
+/// Emitted when a stuck transaction was replaced through `cancel_tx` and the replacement mined
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TxCancelled {
+    pub hash: TxHash,
+    /// The no-op transaction that took its nonce
+    pub replacement: TxHash,
+}
+
+/// Handle for a fire-and-forget write, redeemable with [`StylusClient::await_async`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AsyncWriteId(u64);
+
+#[derive(Debug, Clone)]
+enum AsyncWrite {
+    Submitting,
+    Sent(TxHash),
+    Rejected(String),
+}
+
+#[derive(Debug, Clone)]
+enum TxOutcome {
+    Confirmed(TxHash, Box<TransactionReceipt>),
+    /// Hash, reason and whether resubmitting could succeed
+    Failed(TxHash, String, bool),
+    /// Reverted after using up its gas limit, which is carried along
+    OutOfGas(TxHash, U256),
+    /// Never broadcast, e.g. the pre-send simulation reverted
+    Rejected(String),
+    /// Stuck hash and the replacement that mined in its place
+    Cancelled(TxHash, TxHash),
+}
+
+/// In-flight transactions, optionally persisted to disk so they survive a crash
+#[derive(Debug, Clone, Default)]
+pub struct PendingTransactions {
+    transactions: Arc<Mutex<HashMap<TxHash, PendingTx>>>,
+    outcomes: Arc<Mutex<Vec<TxOutcome>>>,
+    persist_path: Option<PathBuf>,
+    /// Fire-and-forget writes with when they last changed state
+    async_writes: Arc<Mutex<HashMap<AsyncWriteId, (AsyncWrite, Instant)>>>,
+    next_async_id: Arc<AtomicU64>,
+}
+
+impl PendingTransactions {
+    /// Create a tracker persisted at `path`, loading transactions left over from a previous run
+    pub fn with_persistence(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let transactions = match fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<Vec<PendingTx>>(&content) {
+                Ok(transactions) => {
+                    if !transactions.is_empty() {
+                        println!("📂 Restored {} pending transactions from {}", transactions.len(), path.display());
+                    }
+                    transactions.into_iter().map(|tx| (tx.hash, tx)).collect()
+                }
+                Err(e) => {
+                    println!("⚠️ Ignoring corrupt pending transaction file {}: {}", path.display(), e);
+                    HashMap::new()
+                }
+            },
+            Err(_) => HashMap::new(),
+        };
+
+        Self {
+            transactions: Arc::new(Mutex::new(transactions)),
+            persist_path: Some(path),
+            ..Default::default()
+        }
+    }
+
+    /// Start tracking a submitted transaction
+    pub fn track(&self, hash: TxHash, function: &str, depth: usize) {
+        self.insert(hash, function, depth, None);
+    }
+
+    /// Start tracking a transaction sent to take the nonce of the stuck `replaces`
+    pub(crate) fn track_replacement(&self, hash: TxHash, function: &str, replaces: TxHash) {
+        self.insert(hash, function, 0, Some(replaces));
+    }
+
+    fn insert(&self, hash: TxHash, function: &str, depth: usize, replaces: Option<TxHash>) {
+        let submitted_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        if let Ok(mut transactions) = self.transactions.lock() {
+            let tx = PendingTx { hash, function: function.to_string(), submitted_at, depth, replaces };
+            transactions.insert(hash, tx);
+            self.persist(&transactions);
+        }
+    }
+
+    fn finish(&self, hash: TxHash, outcome: TxOutcome) {
+        let mut cancelled = None;
+        if let Ok(mut transactions) = self.transactions.lock() {
+            // Its replacement is still pending; the cancellation reports the outcome instead
+            let dropped = matches!(outcome, TxOutcome::Failed(_, _, true));
+            if dropped && transactions.values().any(|tx| tx.replaces == Some(hash)) {
+                return;
+            }
+            // Dropped by `reset`, nobody is waiting for this outcome anymore
+            let Some(tx) = transactions.remove(&hash) else {
+                return;
+            };
+            if let (Some(stuck), TxOutcome::Confirmed(..)) = (tx.replaces, &outcome) {
+                transactions.remove(&stuck);
+                cancelled = Some(TxOutcome::Cancelled(stuck, hash));
+            }
+            self.persist(&transactions);
+        }
+        if let Ok(mut outcomes) = self.outcomes.lock() {
+            outcomes.push(outcome);
+            outcomes.extend(cancelled);
+        }
+    }
+
+    /// Stop tracking every transaction and return an empty tracker persisted at the same path
+    pub(crate) fn reset(&self) -> PendingTransactions {
+        if let Ok(mut transactions) = self.transactions.lock() {
+            transactions.clear();
+        }
+        let fresh = PendingTransactions { persist_path: self.persist_path.clone(), ..Default::default() };
+        if let Ok(transactions) = fresh.transactions.lock() {
+            fresh.persist(&transactions);
+        }
+        fresh
+    }
+
+    /// Report a write that failed before it got a transaction hash
+    fn reject(&self, reason: String) {
+        if let Ok(mut outcomes) = self.outcomes.lock() {
+            outcomes.push(TxOutcome::Rejected(reason));
+        }
+    }
+
+    /// Register a fire-and-forget write that is about to be submitted
+    pub(crate) fn start_async(&self) -> AsyncWriteId {
+        let id = AsyncWriteId(self.next_async_id.fetch_add(1, Ordering::SeqCst));
+        self.set_async(id, AsyncWrite::Submitting);
+        id
+    }
+
+    pub(crate) fn async_sent(&self, id: AsyncWriteId, hash: TxHash) {
+        self.set_async(id, AsyncWrite::Sent(hash));
+    }
+
+    /// Record that the write behind `id` never got a hash, and report it as `TxFailed`
+    pub(crate) fn async_rejected(&self, id: AsyncWriteId, reason: String) {
+        self.set_async(id, AsyncWrite::Rejected(reason.clone()));
+        self.reject(reason);
+    }
+
+    fn set_async(&self, id: AsyncWriteId, write: AsyncWrite) {
+        if let Ok(mut writes) = self.async_writes.lock() {
+            writes.insert(id, (write, Instant::now()));
+            // Most fire-and-forget writes are never awaited; forget them a while after they settle
+            writes.retain(|_, (write, updated)| updated.elapsed() < ASYNC_WRITE_RETENTION || !self.is_settled(write));
+        }
+    }
+
+    /// Whether nothing more will happen to `write`: it was rejected, or its transaction
+    /// is no longer pending
+    fn is_settled(&self, write: &AsyncWrite) -> bool {
+        match write {
+            AsyncWrite::Submitting => false,
+            AsyncWrite::Rejected(_) => true,
+            AsyncWrite::Sent(hash) => !self.contains(hash),
+        }
+    }
+
+    fn persist(&self, transactions: &HashMap<TxHash, PendingTx>) {
+        let Some(path) = &self.persist_path else {
+            return;
+        };
+        let entries: Vec<&PendingTx> = transactions.values().collect();
+        let result = serde_json::to_string_pretty(&entries)
+            .map_err(|e| e.to_string())
+            .and_then(|content| fs::write(path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            println!("⚠️ Failed to persist pending transactions to {}: {}", path.display(), e);
+        }
+    }
+
+    /// Snapshot of the transactions still waiting to be mined
+    pub fn all(&self) -> Vec<PendingTx> {
+        self.transactions
+            .lock()
+            .map(|transactions| transactions.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn contains(&self, hash: &TxHash) -> bool {
+        self.transactions
+            .lock()
+            .map(|transactions| transactions.contains_key(hash))
+            .unwrap_or(false)
+    }
+
+    pub fn len(&self) -> usize {
+        self.transactions.lock().map(|transactions| transactions.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Poll `hash` on a background thread until it is `depth` blocks deep or dropped
+    pub(crate) fn watch(&self, hash: TxHash, provider: Arc<Provider<StylusHttp>>, depth: usize, runtime: StylusRuntime) {
+        let pending = self.clone();
+        std::thread::spawn(move || {
+            let pending_tx = PendingTransaction::new(hash, &provider).confirmations(depth + 1);
+            let result = match runtime.block_on(pending_tx) {
+                Ok(result) => result,
+                Err(e) => {
+                    pending.finish(hash, TxOutcome::Failed(hash, format!("Failed to start runtime: {}", e), true));
+                    return;
+                }
+            };
+            let outcome = match result {
+                Ok(Some(receipt)) if receipt.status == Some(U64::from(1)) => TxOutcome::Confirmed(hash, Box::new(receipt)),
+                Ok(Some(receipt)) => {
+                    let gas_limit = runtime
+                        .block_on(provider.get_transaction(hash))
+                        .ok()
+                        .and_then(|tx| tx.ok().flatten())
+                        .map(|tx| tx.gas);
+                    match (receipt.gas_used, gas_limit) {
+                        (Some(used), Some(limit)) if is_out_of_gas(used, limit) => TxOutcome::OutOfGas(hash, limit),
+                        _ => TxOutcome::Failed(hash, "Transaction reverted".to_string(), false),
+                    }
+                }
+                Ok(None) => TxOutcome::Failed(hash, "Transaction dropped from mempool".to_string(), true),
+                Err(e) => TxOutcome::Failed(hash, e.to_string(), true),
+            };
+            pending.finish(hash, outcome);
+        });
+    }
+
+    fn drain_outcomes(&self) -> Vec<TxOutcome> {
+        self.outcomes
+            .lock()
+            .map(|mut outcomes| std::mem::take(&mut *outcomes))
+            .unwrap_or_default()
+    }
+}
+
+impl StylusClient {
+    /// Wait for the transaction behind a fire-and-forget write to be mined.
+    ///
+    /// Fails if `id` is unknown (already awaited, or settled more than five minutes
+    /// before the call and forgotten), if the write was rejected
+    /// before broadcast, if the transaction reverts or is dropped, or with
+    /// `StylusError::Timeout` when `timeout` elapses first.
+    pub fn await_async(&self, id: AsyncWriteId, timeout: Duration) -> Result<TransactionReceipt> {
+        let started = Instant::now();
+        let hash = self.await_submitted(id, timeout)?;
+        self.await_mined(id, hash, timeout.saturating_sub(started.elapsed()), timeout)
+    }
+
+    /// First half of [`await_async`](Self::await_async): the hash once the write is
+    /// broadcast, or why it never was
+    pub(crate) fn await_submitted(&self, id: AsyncWriteId, timeout: Duration) -> Result<TxHash> {
+        let started = Instant::now();
+        loop {
+            let write = self
+                .pending_transactions
+                .async_writes
+                .lock()
+                .map_err(|_| eyre::eyre!("Async write state poisoned"))?
+                .get(&id)
+                .map(|(write, _)| write.clone());
+            match write {
+                None => return Err(eyre::eyre!("Unknown async write {:?}", id)),
+                Some(AsyncWrite::Rejected(reason)) => return Err(eyre::eyre!(reason)),
+                Some(AsyncWrite::Sent(hash)) => return Ok(hash),
+                Some(AsyncWrite::Submitting) if started.elapsed() >= timeout => {
+                    return Err(StylusError::Timeout { method: "await_async".to_string(), timeout }.into())
+                }
+                Some(AsyncWrite::Submitting) => std::thread::sleep(ASYNC_SUBMIT_POLL),
+            }
+        }
+    }
+
+    /// Second half of [`await_async`](Self::await_async): the receipt of the broadcast
+    /// `hash`, waiting at most `remaining`. `timeout` is the overall limit, for the error.
+    pub(crate) fn await_mined(&self, id: AsyncWriteId, hash: TxHash, remaining: Duration, timeout: Duration) -> Result<TransactionReceipt> {
+        let provider = self
+            .provider
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Contract not initialized"))?;
+        // Not routed through `run_rpc`: a long wait for mining isn't a slow RPC call
+        let pending = PendingTransaction::new(hash, provider).interval(self.confirmation_poll_interval);
+        let receipt = self
+            .runtime
+            .block_on(async { tokio::time::timeout(remaining, pending).await })?
+            .map_err(|_| StylusError::Timeout { method: "await_async".to_string(), timeout })??;
+        if let Ok(mut writes) = self.pending_transactions.async_writes.lock() {
+            writes.remove(&id);
+        }
+        match receipt {
+            Some(receipt) if receipt.status == Some(U64::from(1)) => Ok(receipt),
+            Some(_) => Err(eyre::eyre!("Transaction {:?} reverted", hash)),
+            None => Err(eyre::eyre!("Transaction {:?} dropped from mempool", hash)),
+        }
+    }
+}
+
+/// How often `await_async` checks whether a write has been submitted yet
+const ASYNC_SUBMIT_POLL: Duration = Duration::from_millis(50);
+
+/// How long a settled fire-and-forget write stays redeemable with `await_async`
+const ASYNC_WRITE_RETENTION: Duration = Duration::from_secs(300);
+
+/// Run condition: no write is waiting to be mined, e.g. to enable an action button
+pub fn no_pending_txs(client: Option<Res<StylusClient>>) -> bool {
+    client.is_none_or(|client| client.pending_transactions.is_empty())
+}
+
+/// Run condition: at least one write is waiting to be mined
+pub fn has_pending_txs(client: Option<Res<StylusClient>>) -> bool {
+    !no_pending_txs(client)
+}
+
+/// Run condition: more than `count` writes are waiting to be mined.
+/// `more_than_pending_txs(0)` is the same as [`has_pending_txs`].
+pub fn more_than_pending_txs(count: usize) -> impl FnMut(Option<Res<StylusClient>>) -> bool + Clone {
+    move |client| client.is_some_and(|client| client.pending_transactions.len() > count)
+}
+
+/// Turn finished pending transactions into `TxConfirmed` / `TxFailed` events,
+/// also publishing them to `subscribe_events` receivers
+pub fn drain_pending_transactions(
+    client: Option<Res<StylusClient>>,
+    mut confirmed: EventWriter<TxConfirmed>,
+    mut failed: EventWriter<TxFailed>,
+    mut cancelled: EventWriter<TxCancelled>,
+    mut gas_retried: EventWriter<TxGasRetried>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+
+    for outcome in client.pending_transactions.drain_outcomes() {
+        match outcome {
+            TxOutcome::Confirmed(hash, receipt) => {
+                client.tx_history.resolve(hash, TxStatus::Confirmed);
+                let events = client.decode_receipt_events(&receipt);
+                let event = TxConfirmed { hash, receipt: *receipt, events };
+                client.event_stream.publish(StylusEvent::Confirmed(Box::new(event.clone())));
+                confirmed.send(event);
+            }
+            TxOutcome::Failed(hash, reason, retryable) => {
+                client.tx_history.resolve(hash, TxStatus::Failed { reason: reason.clone(), retryable });
+                let event = TxFailed { hash: Some(hash), reason };
+                client.event_stream.publish(StylusEvent::Failed(event.clone()));
+                failed.send(event);
+            }
+            TxOutcome::OutOfGas(hash, gas_limit) => {
+                let reason = format!("Transaction ran out of gas (limit {})", gas_limit);
+                // Sending the same call with the same limit would fail again
+                client.tx_history.resolve(hash, TxStatus::Failed { reason: reason.clone(), retryable: false });
+                if let Some(retry) = client.retry_out_of_gas(hash, gas_limit) {
+                    gas_retried.send(retry);
+                }
+                let event = TxFailed { hash: Some(hash), reason };
+                client.event_stream.publish(StylusEvent::Failed(event.clone()));
+                failed.send(event);
+            }
+            TxOutcome::Rejected(reason) => {
+                let event = TxFailed { hash: None, reason };
+                client.event_stream.publish(StylusEvent::Failed(event.clone()));
+                failed.send(event);
+            }
+            TxOutcome::Cancelled(hash, replacement) => {
+                client.tx_history.resolve(hash, TxStatus::Cancelled { replacement });
+                let event = TxCancelled { hash, replacement };
+                client.event_stream.publish(StylusEvent::Cancelled(event));
+                cancelled.send(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pretend `id` last changed state longer ago than the retention
+    fn age(pending: &PendingTransactions, id: AsyncWriteId) {
+        let mut writes = pending.async_writes.lock().unwrap();
+        writes.get_mut(&id).unwrap().1 = Instant::now().checked_sub(ASYNC_WRITE_RETENTION * 2).unwrap();
+    }
+
+    #[test]
+    fn settled_async_writes_are_forgotten() {
+        let client = StylusClient::default();
+        let pending = &client.pending_transactions;
+        let rejected = pending.start_async();
+        pending.async_rejected(rejected, "simulation reverted".to_string());
+        let mined = pending.start_async();
+        pending.async_sent(mined, TxHash::repeat_byte(1));
+        let recent = pending.start_async();
+        pending.async_rejected(recent, "nonce too low".to_string());
+        age(pending, rejected);
+        age(pending, mined);
+
+        pending.start_async();
+        let writes = pending.async_writes.lock().unwrap();
+        assert!(!writes.contains_key(&rejected) && !writes.contains_key(&mined));
+        assert!(writes.contains_key(&recent));
+        drop(writes);
+
+        let error = client.await_submitted(rejected, Duration::ZERO).unwrap_err();
+        assert_eq!(error.to_string(), format!("Unknown async write {:?}", rejected));
+        assert_eq!(client.await_submitted(recent, Duration::ZERO).unwrap_err().to_string(), "nonce too low");
+    }
+
+    #[test]
+    fn async_writes_still_pending_are_kept() {
+        let pending = PendingTransactions::default();
+        let hash = TxHash::repeat_byte(2);
+        let id = pending.start_async();
+        pending.track(hash, "incrementSword", 0);
+        pending.async_sent(id, hash);
+        age(&pending, id);
+
+        pending.start_async();
+        assert!(pending.async_writes.lock().unwrap().contains_key(&id));
+    }
+}
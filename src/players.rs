@@ -0,0 +1,116 @@
+use ethers::abi::Tokenizable;
+use ethers::types::{Address, U256};
+use eyre::Result;
+
+use crate::{StylusClient, StylusError};
+
+/// Players read per multicall request, keeping each `eth_call` well under node gas caps
+const PLAYERS_PER_MULTICALL: usize = 200;
+
+impl StylusClient {
+    /// Swords of each color incremented by `player`, from `swordsByPlayer`
+    pub fn swords_by_player(&self, player: Address) -> Result<(u64, u64, u64)> {
+        let contract = self.read_contract.as_ref().ok_or(StylusError::NotInitialized)?;
+        let (a, b, c) = self.call_contract(contract.swords_by_player(player))?;
+        Ok((self.u256_to_u64(a)?, self.u256_to_u64(b)?, self.u256_to_u64(c)?))
+    }
+
+    /// [`swords_by_player`](Self::swords_by_player) for many players, e.g. a leaderboard,
+    /// in the same order as `players`.
+    ///
+    /// Reads go through Multicall3 in chunks of 200 players per request, at the
+    /// configured read block tag, or one by one when no multicall contract is deployed.
+    pub fn swords_by_players(&self, players: &[Address]) -> Result<Vec<(u64, u64, u64)>> {
+        let contract = self.read_contract.as_ref().ok_or(StylusError::NotInitialized)?;
+        if players.is_empty() {
+            return Ok(Vec::new());
+        }
+        let Some(multicall) = self.multicall()? else {
+            return players.iter().map(|player| self.swords_by_player(*player)).collect();
+        };
+
+        let mut swords = Vec::with_capacity(players.len());
+        for chunk in players.chunks(PLAYERS_PER_MULTICALL) {
+            let mut multicall = multicall.clone();
+            for player in chunk {
+                multicall.add_call(contract.swords_by_player(*player), false);
+            }
+            let results = self.with_read_block(|block| {
+                let mut multicall = multicall.clone();
+                multicall.block = block;
+                self.block_on_rpc("multicall", multicall.call_raw())
+            })?;
+            for (player, result) in chunk.iter().zip(results) {
+                let token = result
+                    .map_err(|data| eyre::eyre!("swordsByPlayer({:?}) reverted with data {}", player, data))?;
+                let (a, b, c) = <(U256, U256, U256)>::from_token(token)?;
+                swords.push((self.u256_to_u64(a)?, self.u256_to_u64(b)?, self.u256_to_u64(c)?));
+            }
+        }
+        Ok(swords)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{aggregate3, call_data, contract_address, encoded, MockNode};
+    use ethers::abi::{self, ParamType, Token};
+    use ethers::prelude::MULTICALL_ADDRESS;
+    use serde_json::Value;
+
+    /// Counts the mock contract reports for `player`: its address byte times 1, 2 and 3
+    fn counts_of(player: Address) -> Vec<Token> {
+        let n = player.as_bytes()[0] as u64;
+        vec![Token::Uint(n.into()), Token::Uint((2 * n).into()), Token::Uint((3 * n).into())]
+    }
+
+    /// `swordsByPlayer` call data's player
+    fn player_of(args: &[u8]) -> Address {
+        abi::decode(&[ParamType::Address], args).unwrap()[0].clone().into_address().unwrap()
+    }
+
+    fn node(multicall_deployed: bool) -> MockNode {
+        MockNode::start(move |method, params| match method {
+            "eth_getCode" => Ok(Value::String(if multicall_deployed { "0x6080" } else { "0x" }.to_string())),
+            "eth_chainId" => Ok(Value::String("0x64aba".to_string())),
+            _ => {
+                let (_, args) = call_data(params);
+                let to = params[0]["to"].as_str().unwrap_or_default();
+                if to == format!("{:?}", MULTICALL_ADDRESS) {
+                    Ok(aggregate3(&args, |data| counts_of(player_of(&data[4..]))))
+                } else {
+                    assert!(!multicall_deployed, "called the contract directly despite multicall");
+                    assert_eq!(to, format!("{:?}", contract_address()));
+                    Ok(encoded(&counts_of(player_of(&args))))
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn swords_by_player_decodes_the_counts() {
+        assert_eq!(node(false).client().swords_by_player(Address::repeat_byte(2)).unwrap(), (2, 4, 6));
+    }
+
+    #[test]
+    fn swords_by_players_batches_through_multicall() {
+        let players: Vec<Address> = (1..=3).map(Address::repeat_byte).collect();
+        let swords = node(true).client().swords_by_players(&players).unwrap();
+        assert_eq!(swords, [(1, 2, 3), (2, 4, 6), (3, 6, 9)]);
+    }
+
+    #[test]
+    fn swords_by_players_falls_back_to_sequential_calls() {
+        let players: Vec<Address> = (1..=3).map(Address::repeat_byte).collect();
+        let swords = node(false).client().swords_by_players(&players).unwrap();
+        assert_eq!(swords, [(1, 2, 3), (2, 4, 6), (3, 6, 9)]);
+    }
+
+    #[test]
+    fn swords_by_players_skips_empty_lists() {
+        let node = MockNode::start(|method, _| panic!("unexpected {}", method));
+        assert!(node.client().swords_by_players(&[]).unwrap().is_empty());
+        assert!(StylusClient::default().swords_by_players(&[]).is_err());
+    }
+}
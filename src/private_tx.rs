@@ -0,0 +1,81 @@
+use ethers::prelude::{Middleware, ProviderError, Signer};
+use ethers::types::{transaction::eip2718::TypedTransaction, TxHash};
+use eyre::Result;
+use serde_json::json;
+
+use crate::{StylusClient, StylusSigner};
+
+impl StylusClient {
+    /// Sign `tx` locally and submit it through `eth_sendPrivateTransaction` at `url`,
+    /// keeping it out of the public mempool until it is included.
+    ///
+    /// Returns `Ok(None)` when the endpoint can't be reached, so the caller can send the
+    /// write publicly instead. Every other failure is an error: a signing failure, a
+    /// JSON-RPC rejection, or a timeout after the request went out, when the endpoint
+    /// may already hold the transaction.
+    pub(crate) fn send_private_transaction(&self, url: &str, signer: &StylusSigner, mut tx: TypedTransaction) -> Result<Option<TxHash>> {
+        let endpoint = self.http_provider(url)?;
+        let result = self.run_rpc("eth_sendPrivateTransaction", async {
+            signer.fill_transaction(&mut tx, None).await?;
+            let signature = signer.signer().sign_transaction(&tx).await?;
+            let raw = tx.rlp_signed(&signature);
+            match endpoint.request("eth_sendPrivateTransaction", [json!({ "tx": raw })]).await {
+                Ok(tx_hash) => Ok(Some(tx_hash)),
+                Err(ProviderError::HTTPError(e)) if e.is_connect() => {
+                    println!("⚠️ Private transaction endpoint unreachable: {}", e);
+                    Ok(None)
+                }
+                Err(e) => Err(e.into()),
+            }
+        })?;
+        result.map_err(|e| self.log_error("eth_sendPrivateTransaction", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{contract_address, MockNode};
+    use ethers::providers::JsonRpcError;
+    use ethers::types::TransactionRequest;
+    use serde_json::Value;
+
+    /// A transaction with every field set, so signing it needs no RPC call
+    fn filled_tx() -> TypedTransaction {
+        TransactionRequest::new().to(contract_address()).gas(21_000).gas_price(1).nonce(0).into()
+    }
+
+    fn send_to(url: &str) -> Result<Option<TxHash>> {
+        let node = MockNode::start(|method, _| panic!("unexpected {}", method));
+        let client = node.signing_client();
+        let signer = client.contract_client.clone().unwrap();
+        client.send_private_transaction(url, &signer, filled_tx())
+    }
+
+    #[test]
+    fn accepted_private_transactions_return_the_hash() {
+        let endpoint = MockNode::start(|method, params| {
+            assert_eq!(method, "eth_sendPrivateTransaction");
+            assert!(params[0]["tx"].as_str().is_some_and(|raw| raw.starts_with("0x")));
+            Ok(Value::String(format!("{:?}", TxHash::repeat_byte(7))))
+        });
+        assert_eq!(send_to(endpoint.url()).unwrap(), Some(TxHash::repeat_byte(7)));
+    }
+
+    #[test]
+    fn unreachable_endpoints_allow_the_public_fallback() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        assert_eq!(send_to(&url).unwrap(), None);
+    }
+
+    #[test]
+    fn rejections_are_returned_instead_of_falling_back() {
+        let endpoint = MockNode::start(|_, _| {
+            Err(JsonRpcError { code: -32000, message: "bundle rejected".to_string(), data: None })
+        });
+        let error = send_to(endpoint.url()).unwrap_err();
+        assert!(format!("{:#}", error).contains("bundle rejected"), "{:#}", error);
+    }
+}
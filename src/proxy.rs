@@ -0,0 +1,32 @@
+use ethers::prelude::{Middleware, Provider, ProviderError};
+use ethers::types::{Address, H256};
+use eyre::Result;
+
+use crate::{StylusClient, StylusError, StylusHttp};
+
+/// EIP-1967 implementation slot, `keccak256("eip1967.proxy.implementation") - 1`
+const IMPLEMENTATION_SLOT: H256 = H256([
+    0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d, 0xca, 0x3e, 0x20,
+    0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+]);
+
+/// Implementation address stored in `proxy`'s EIP-1967 slot, `None` when the slot is empty
+pub(crate) async fn read_implementation(
+    provider: &Provider<StylusHttp>,
+    proxy: Address,
+) -> Result<Option<Address>, ProviderError> {
+    let slot = provider.get_storage_at(proxy, IMPLEMENTATION_SLOT, None).await?;
+    let implementation = Address::from_slice(&slot.as_bytes()[12..]);
+    Ok(Some(implementation).filter(|address| !address.is_zero()))
+}
+
+impl StylusClient {
+    /// Implementation behind an EIP-1967 (transparent or UUPS) proxy at `proxy`.
+    ///
+    /// `None` means the slot is empty, i.e. `proxy` is not an EIP-1967 proxy. Calls
+    /// should still go to the proxy; this is for diagnostics and explorer links.
+    pub fn proxy_implementation(&self, proxy: Address) -> Result<Option<Address>> {
+        let provider = self.provider.as_ref().ok_or(StylusError::NotInitialized)?;
+        self.block_on_rpc("eth_getStorageAt", read_implementation(provider, proxy))
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use ethers::contract::multicall_contract::Call3;
+use ethers::types::TxHash;
+use eyre::Result;
+
+use crate::{SequentialWriteQueue, StylusClient, StylusError};
+
+/// Increment requests collected during a frame and submitted together at its end.
+///
+/// Requests are sent in the `Last` schedule, so they go out up to one frame later
+/// than a direct `increment_sword` call.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SwordIncrementQueue {
+    colors: Vec<u8>,
+}
+
+impl SwordIncrementQueue {
+    /// Request an increment of `color` at the end of this frame
+    pub fn push(&mut self, color: u8) {
+        self.colors.push(color);
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+}
+
+impl StylusClient {
+    /// Increment several colors in a single Multicall3 transaction.
+    ///
+    /// The contract sees the multicall contract as the caller, not our wallet, so this
+    /// only suits increments that don't depend on `msg.sender`. Falls back to one
+    /// transaction per color when no multicall contract is deployed.
+    pub fn increment_swords_batched(&self, colors: &[u8]) -> Result<Vec<TxHash>> {
+        let contract = self.contract.as_ref().ok_or(StylusError::NotInitialized)?;
+        let multicall = match colors {
+            [] => return Ok(Vec::new()),
+            [_] => None,
+            _ => self.multicall()?,
+        };
+        let Some(multicall) = multicall else {
+            return colors
+                .iter()
+                .map(|color| self.send_contract(contract.increment_sword(self.u8_to_u256(*color))))
+                .collect();
+        };
+        let target = self.contract_address.ok_or(StylusError::NotInitialized)?;
+        let calls = colors
+            .iter()
+            .map(|color| Call3 {
+                target,
+                allow_failure: false,
+                call_data: contract.increment_sword(self.u8_to_u256(*color)).calldata().unwrap_or_default(),
+            })
+            .collect();
+        let tx_hash = self.send_transaction_as("incrementSword", multicall.contract.aggregate_3(calls).tx)?;
+        Ok(vec![tx_hash])
+    }
+}
+
+/// Submit the frame's queued increments, as one transaction when coalescing is enabled,
+/// or hand them to `SequentialWriteQueue` in sequential mode
+pub(crate) fn submit_sword_increments(
+    client: Option<Res<StylusClient>>,
+    mut queue: ResMut<SwordIncrementQueue>,
+    mut sequential: ResMut<SequentialWriteQueue>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+    if queue.is_empty() {
+        return;
+    }
+    let colors = std::mem::take(&mut queue.colors);
+    if client.sequential_writes {
+        colors.into_iter().for_each(|color| sequential.push(color));
+        return;
+    }
+    let client = client.clone();
+    std::thread::spawn(move || {
+        let result = if client.coalesce_increments {
+            client.increment_swords_batched(&colors).map(|_| ())
+        } else {
+            colors.iter().try_for_each(|color| client.increment_sword(*color))
+        };
+        if let Err(e) = result {
+            println!("⚠️ Failed to submit {} queued increments: {}", colors.len(), e);
+        }
+    });
+}
@@ -0,0 +1,248 @@
+use ethers::providers::{JsonRpcError, ProviderError, RpcError};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{StylusClient, StylusError};
+
+/// JSON-RPC error codes providers use for rate limiting (Alchemy mirrors HTTP 429, Infura uses -32005)
+const RATE_LIMIT_CODES: &[i64] = &[429, -32005];
+
+/// Fragments of rate-limit errors from providers that answer with a plain HTTP 429 body
+const RATE_LIMIT_MESSAGES: &[&str] = &["too many requests", "rate limit", "request limit"];
+
+/// Fields of a JSON-RPC error's `data` providers put their backoff hint in, in seconds
+const RETRY_HINT_FIELDS: &[&str] = &["retry_after", "retryAfter", "backoff_seconds"];
+
+/// Longest delay honored from a `Retry-After` header or backoff hint; larger ones are clamped to it
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(3_600);
+
+/// Month abbreviations of HTTP-dates, in calendar order
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// When the endpoint asked us to resume sending, shared by clones of the client
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RateLimitCooldown(Arc<Mutex<Option<Instant>>>);
+
+impl RateLimitCooldown {
+    /// Time left until the endpoint accepts requests again, `None` once it does
+    pub(crate) fn remaining(&self) -> Option<Duration> {
+        let until = (*self.0.lock().ok()?)?;
+        until.checked_duration_since(Instant::now()).filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Hold off every call for `retry_after`, keeping a later deadline already set
+    fn start(&self, retry_after: Duration) {
+        if let Ok(mut until) = self.0.lock() {
+            let resume = Instant::now() + retry_after;
+            *until = Some(until.map_or(resume, |until| until.max(resume)));
+        }
+    }
+}
+
+/// A `Retry-After` value, either delay seconds (`"120"`) or an HTTP-date
+/// (`"Wed, 21 Oct 2015 07:28:00 GMT"`). Dates in the past give a zero delay, and
+/// delays are capped at an hour.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds).min(MAX_RETRY_AFTER));
+    }
+    let resume = http_date_secs(value)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some(Duration::from_secs(resume.saturating_sub(now)).min(MAX_RETRY_AFTER))
+}
+
+/// The delay from `response`'s `Retry-After` header, if it has a valid one
+pub(crate) fn retry_after_of(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after)
+}
+
+/// Unix seconds of an IMF-fixdate such as `Wed, 21 Oct 2015 07:28:00 GMT`
+fn http_date_secs(value: &str) -> Option<u64> {
+    let [_, day, month, year, time, "GMT"] = value.split_whitespace().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let day: u64 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|name| *name == month)? as u64 + 1;
+    let year: u64 = year.parse().ok()?;
+    let [hour, minute, second] = time.split(':').map(|part| part.parse::<u64>().ok()).collect::<Option<Vec<_>>>()?[..] else {
+        return None;
+    };
+    if year < 1970 || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar, with years starting in March
+    let (year, month) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era_day = 365 * year + year / 4 - year / 100 + year / 400 + (153 * month + 2) / 5 + day - 1;
+    let days = era_day.checked_sub(719_468)?;
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+/// The backoff hint in a rate-limit JSON-RPC error's `data`, if the provider sent one
+fn retry_hint(error: &JsonRpcError) -> Option<Duration> {
+    let data = error.data.as_ref()?;
+    RETRY_HINT_FIELDS
+        .iter()
+        .find_map(|field| data.get(*field)?.as_f64())
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds.min(MAX_RETRY_AFTER.as_secs_f64())).ok())
+}
+
+/// `Some(retry_after)` when `error` says the endpoint is rate limiting us
+fn rate_limit_of(error: &eyre::Report) -> Option<Option<Duration>> {
+    if let Some(StylusError::RateLimited { retry_after }) = error.downcast_ref::<StylusError>() {
+        return Some(*retry_after);
+    }
+    let response = error.downcast_ref::<ProviderError>().and_then(RpcError::as_error_response);
+    if let Some(response) = response.filter(|response| RATE_LIMIT_CODES.contains(&response.code)) {
+        return Some(retry_hint(response));
+    }
+    let message = format!("{:#}", error).to_lowercase();
+    RATE_LIMIT_MESSAGES
+        .iter()
+        .any(|pattern| message.contains(pattern))
+        .then_some(None)
+}
+
+impl StylusClient {
+    /// Turn a rate-limit failure into `StylusError::RateLimited`, pausing calls for
+    /// the delay the endpoint asked for. Other errors pass through unchanged.
+    pub(crate) fn classify_rate_limit(&self, method: &str, error: eyre::Report) -> eyre::Report {
+        let Some(retry_after) = rate_limit_of(&error) else {
+            return error;
+        };
+        // Refused by the running cooldown without reaching the endpoint
+        if error.downcast_ref::<StylusError>().is_some() && self.rate_limit_cooldown.remaining().is_some() {
+            return error;
+        }
+        match retry_after {
+            Some(delay) => {
+                println!("⏳ {} was rate limited, pausing RPC calls for {:?}", method, delay);
+                self.rate_limit_cooldown.start(delay);
+            }
+            None => println!("⏳ {} was rate limited: {:#}", method, error),
+        }
+        StylusError::RateLimited { retry_after }.into()
+    }
+}
+
+/// `response` unless its status is an error; a 429 becomes `StylusError::RateLimited`
+/// with the delay from its `Retry-After` header
+pub(crate) fn check_status(response: reqwest::Response) -> eyre::Result<reqwest::Response> {
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(StylusError::RateLimited { retry_after: retry_after_of(&response) }.into());
+    }
+    Ok(response.error_for_status()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_delta_seconds() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("-5"), None);
+        assert_eq!(parse_retry_after("1.5"), None);
+        assert_eq!(parse_retry_after(""), None);
+    }
+
+    #[test]
+    fn retry_after_http_date() {
+        // Dates in the past mean "now"
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        let in_a_minute = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 60;
+        let date = format_http_date(in_a_minute);
+        let delay = parse_retry_after(&date).unwrap().as_secs();
+        assert!((58..=60).contains(&delay), "{} gave {}s", date, delay);
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn retry_after_is_capped() {
+        assert_eq!(parse_retry_after("18446744073709551615"), Some(MAX_RETRY_AFTER));
+        assert_eq!(parse_retry_after("Fri, 01 Jan 2100 00:00:00 GMT"), Some(MAX_RETRY_AFTER));
+    }
+
+    /// IMF-fixdate of `secs`, the inverse of `http_date_secs` (weekday left as "Mon")
+    fn format_http_date(secs: u64) -> String {
+        let (days, time) = (secs / 86_400, secs % 86_400);
+        // Civil date from days since 1970-01-01, years starting in March
+        let era_day = days + 719_468;
+        let (era, day_of_era) = (era_day / 146_097, era_day % 146_097);
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let month = if month < 10 { month + 3 } else { month - 9 };
+        let year = era * 400 + year_of_era + u64::from(month <= 2);
+        format!(
+            "Mon, {:02} {} {} {:02}:{:02}:{:02} GMT",
+            day,
+            MONTHS[month as usize - 1],
+            year,
+            time / 3_600,
+            time % 3_600 / 60,
+            time % 60
+        )
+    }
+
+    #[test]
+    fn http_date_to_unix_seconds() {
+        assert_eq!(http_date_secs("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(http_date_secs("Wed, 21 Oct 2015 07:28:00 GMT"), Some(1_445_412_480));
+        assert_eq!(http_date_secs("Tue, 29 Feb 2000 12:00:00 GMT"), Some(951_825_600));
+        assert_eq!(http_date_secs("Sun, 31 Dec 2023 23:59:59 GMT"), Some(1_704_067_199));
+    }
+
+    #[test]
+    fn http_date_rejects_other_formats() {
+        assert_eq!(http_date_secs("Wed, 21 Oct 2015 07:28:00 UTC"), None);
+        assert_eq!(http_date_secs("Wednesday, 21-Oct-15 07:28:00 GMT"), None);
+        assert_eq!(http_date_secs("Wed, 21 Foo 2015 07:28:00 GMT"), None);
+        assert_eq!(http_date_secs("Wed, 32 Oct 2015 07:28:00 GMT"), None);
+        assert_eq!(http_date_secs("Wed, 21 Oct 2015 24:00:00 GMT"), None);
+        assert_eq!(http_date_secs("Wed, 21 Oct 1969 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn rate_limit_codes_and_hints_are_recognized() {
+        let error = |code: i64, data: Option<serde_json::Value>| {
+            eyre::Report::new(ProviderError::JsonRpcClientError(Box::new(crate::transport::StylusHttpError::JsonRpc(
+                JsonRpcError { code, message: "slow down".to_string(), data },
+            ))))
+        };
+        let hint = Some(serde_json::json!({ "retry_after": 2.5 }));
+        assert_eq!(rate_limit_of(&error(429, hint)), Some(Some(Duration::from_millis(2_500))));
+        assert_eq!(rate_limit_of(&error(-32005, None)), Some(None));
+        assert_eq!(rate_limit_of(&error(-32000, None)), None);
+        assert_eq!(rate_limit_of(&eyre::eyre!("429 Too Many Requests")), Some(None));
+    }
+
+    #[test]
+    fn huge_or_invalid_hints_do_not_panic() {
+        let hint = |value: serde_json::Value| {
+            let data = Some(serde_json::json!({ "retry_after": value }));
+            retry_hint(&JsonRpcError { code: 429, message: "slow down".to_string(), data })
+        };
+        assert_eq!(hint(serde_json::json!(1e30)), Some(MAX_RETRY_AFTER));
+        assert_eq!(hint(serde_json::json!(-1.0)), None);
+        assert_eq!(hint(serde_json::json!(0.25)), Some(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn rate_limited_client_pauses_calls() {
+        let client = StylusClient::default();
+        let error = client.classify_rate_limit("eth_call", StylusError::RateLimited { retry_after: Some(Duration::from_secs(30)) }.into());
+        assert!(matches!(error.downcast_ref::<StylusError>(), Some(StylusError::RateLimited { .. })));
+        let remaining = client.rate_limit_cooldown.remaining().unwrap();
+        assert!(remaining <= Duration::from_secs(30) && remaining > Duration::from_secs(25));
+    }
+}
@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+use eyre::Result;
+use std::{
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{StylusClient, TxConfirmed};
+
+/// A resource kept in sync with a contract view call.
+///
+/// Register it with [`StylusAppExt::register_stylus_read`]; it is then fetched on
+/// its poll interval and whenever a [`RefreshReads`] event is sent. The resource is
+/// only re-inserted when the fetched value differs from the current one.
+pub trait StylusRead: Resource + PartialEq + Sized {
+    /// Name used in logs and [`ReadsRefreshed`] events
+    const NAME: &'static str;
+
+    /// Perform the (blocking) view call; runs off the main thread
+    fn fetch(client: &StylusClient) -> Result<Self>;
+}
+
+/// Sword counts per color, refreshed from `getSwordCounts`
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SwordCounts(pub u64, pub u64, pub u64);
+
+impl StylusRead for SwordCounts {
+    const NAME: &'static str = "SwordCounts";
+
+    fn fetch(client: &StylusClient) -> Result<Self> {
+        let (a, b, c) = client.get_sword_counts()?;
+        Ok(SwordCounts(a, b, c))
+    }
+}
+
+/// Send to force an immediate re-fetch of every registered read
+#[derive(Event, Debug, Clone, Default)]
+pub struct RefreshReads;
+
+/// Emitted when a fetch requested through [`RefreshReads`] finishes
+#[derive(Event, Debug, Clone)]
+pub struct ReadsRefreshed {
+    pub read: &'static str,
+    /// Why the fetch failed, if it did
+    pub error: Option<String>,
+}
+
+/// Whether the last fetch of a registered read failed, kept alongside `R`.
+///
+/// `R` itself keeps showing its last good value (or the configured fallback), so UI
+/// can keep rendering numbers and show a subtle warning while `is_stale` is set.
+#[derive(Resource, Debug)]
+pub struct ReadStatus<R> {
+    /// Why the last fetch failed, cleared by the next successful one
+    pub last_error: Option<String>,
+    /// Whether the value in `R` is not from the last fetch
+    pub is_stale: bool,
+    _read: PhantomData<R>,
+}
+
+impl<R> Default for ReadStatus<R> {
+    fn default() -> Self {
+        Self {
+            last_error: None,
+            is_stale: false,
+            _read: PhantomData,
+        }
+    }
+}
+
+/// What a registered read shows while its fetches fail
+#[derive(Debug, Clone, Default)]
+pub enum ReadFallback<R> {
+    /// Keep the last good value
+    #[default]
+    KeepLast,
+    /// Show this value instead, e.g. a placeholder the UI recognizes
+    Value(R),
+}
+
+/// Fired at most once per frame when any registered read changed value or a write
+/// confirmed. Opt in with [`StylusAppExt::add_stylus_state_refreshed`].
+#[derive(Event, Debug, Clone, Copy, Default)]
+pub struct StylusStateRefreshed;
+
+/// Whether a read changed this frame, present only once `StylusStateRefreshed` is enabled
+#[derive(Resource, Default)]
+struct StateRefreshTracker {
+    changed: bool,
+}
+
+type FetchSlot<R> = Arc<Mutex<Option<Result<R, String>>>>;
+type FallbackValue<R> = Box<dyn Fn() -> R + Send + Sync>;
+
+#[derive(Resource)]
+struct ReadPoller<R: StylusRead> {
+    /// Explicit poll interval, falls back to the client's `read_poll_interval` when `None`
+    poll_interval: Option<Duration>,
+    since_last_fetch: Duration,
+    in_flight: Option<FetchSlot<R>>,
+    /// Whether the in-flight fetch should report through `ReadsRefreshed`
+    manual: bool,
+    /// Value to show after a failed fetch, `None` keeps the last good one
+    fallback: Option<FallbackValue<R>>,
+    _read: PhantomData<R>,
+}
+
+impl<R: StylusRead> ReadPoller<R> {
+    fn new(poll_interval: Option<Duration>, fallback: Option<FallbackValue<R>>) -> Self {
+        Self {
+            poll_interval,
+            since_last_fetch: Duration::ZERO,
+            in_flight: None,
+            manual: false,
+            fallback,
+            _read: PhantomData,
+        }
+    }
+}
+
+pub trait StylusAppExt {
+    /// Keep `R` in sync with the contract.
+    ///
+    /// With `poll_interval: None` the `read_poll_interval_ms` option from `Stylus.toml`
+    /// is used; when that is unset too, `R` only refreshes on [`RefreshReads`].
+    /// Registering `R` again replaces its poll interval and fallback.
+    fn register_stylus_read<R: StylusRead>(&mut self, poll_interval: Option<Duration>) -> &mut Self;
+
+    /// Like [`register_stylus_read`](Self::register_stylus_read), choosing what `R` shows
+    /// while fetches fail. Failures are reported through [`ReadStatus<R>`] either way.
+    fn register_stylus_read_with_fallback<R: StylusRead + Clone>(
+        &mut self,
+        poll_interval: Option<Duration>,
+        fallback: ReadFallback<R>,
+    ) -> &mut Self;
+
+    /// Emit a coalesced [`StylusStateRefreshed`] event for UI that just re-renders on any change
+    fn add_stylus_state_refreshed(&mut self) -> &mut Self;
+}
+
+impl StylusAppExt for App {
+    fn register_stylus_read<R: StylusRead>(&mut self, poll_interval: Option<Duration>) -> &mut Self {
+        add_stylus_read(self, ReadPoller::<R>::new(poll_interval, None))
+    }
+
+    fn register_stylus_read_with_fallback<R: StylusRead + Clone>(
+        &mut self,
+        poll_interval: Option<Duration>,
+        fallback: ReadFallback<R>,
+    ) -> &mut Self {
+        let fallback: Option<FallbackValue<R>> = match fallback {
+            ReadFallback::KeepLast => None,
+            ReadFallback::Value(value) => Some(Box::new(move || value.clone())),
+        };
+        add_stylus_read(self, ReadPoller::new(poll_interval, fallback))
+    }
+
+    fn add_stylus_state_refreshed(&mut self) -> &mut Self {
+        self.add_event::<StylusStateRefreshed>()
+            .add_event::<TxConfirmed>()
+            .init_resource::<StateRefreshTracker>()
+            .add_systems(PostUpdate, send_state_refreshed)
+    }
+}
+
+fn add_stylus_read<R: StylusRead>(app: &mut App, poller: ReadPoller<R>) -> &mut App {
+    // Registering `R` again, e.g. to give the plugin's `SwordCounts` a fallback, only
+    // replaces its settings; a second system would advance the poll timer twice per frame
+    if app.world().contains_resource::<ReadPoller<R>>() {
+        return app.insert_resource(poller);
+    }
+    app.add_event::<RefreshReads>()
+        .add_event::<ReadsRefreshed>()
+        .insert_resource(poller)
+        .init_resource::<ReadStatus<R>>()
+        .add_systems(Update, poll_stylus_read::<R>)
+}
+
+/// Insert `value` unless `R` already holds it, flagging the change for `StylusStateRefreshed`
+pub(crate) fn insert_if_changed<R: StylusRead>(commands: &mut Commands, value: R) {
+    commands.add(move |world: &mut World| {
+        if world.get_resource::<R>() == Some(&value) {
+            return;
+        }
+        world.insert_resource(value);
+        if let Some(mut tracker) = world.get_resource_mut::<StateRefreshTracker>() {
+            tracker.changed = true;
+        }
+    });
+}
+
+/// Collapse this frame's read changes and confirmations into one `StylusStateRefreshed`
+fn send_state_refreshed(
+    mut tracker: ResMut<StateRefreshTracker>,
+    mut confirmed: EventReader<TxConfirmed>,
+    mut refreshed: EventWriter<StylusStateRefreshed>,
+) {
+    let confirmed = confirmed.read().count() > 0;
+    if std::mem::take(&mut tracker.changed) || confirmed {
+        refreshed.send(StylusStateRefreshed);
+    }
+}
+
+/// Start fetches on the poll interval or on request, at most one in flight per read
+fn poll_stylus_read<R: StylusRead>(
+    mut commands: Commands,
+    client: Option<Res<StylusClient>>,
+    time: Res<Time>,
+    mut poller: ResMut<ReadPoller<R>>,
+    mut status: ResMut<ReadStatus<R>>,
+    mut refresh_requests: EventReader<RefreshReads>,
+    mut refreshed: EventWriter<ReadsRefreshed>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+
+    // Collect a finished fetch
+    let finished = poller
+        .in_flight
+        .as_ref()
+        .and_then(|slot| slot.lock().ok().and_then(|mut result| result.take()));
+    if let Some(result) = finished {
+        poller.in_flight = None;
+        let error = match result {
+            Ok(value) => {
+                insert_if_changed(&mut commands, value);
+                None
+            }
+            Err(e) => {
+                println!("⚠️ Failed to refresh {}: {}", R::NAME, e);
+                if let Some(fallback) = &poller.fallback {
+                    insert_if_changed(&mut commands, fallback());
+                }
+                Some(e)
+            }
+        };
+        status.is_stale = error.is_some();
+        status.last_error.clone_from(&error);
+        if std::mem::take(&mut poller.manual) {
+            refreshed.send(ReadsRefreshed { read: R::NAME, error });
+        }
+    }
+
+    poller.since_last_fetch += time.delta();
+    let manual = refresh_requests.read().count() > 0;
+    let due = poller
+        .poll_interval
+        .or(client.read_poll_interval)
+        .is_some_and(|interval| poller.since_last_fetch >= interval);
+
+    // A manual refresh joins an in-flight fetch rather than starting a second one
+    poller.manual |= manual;
+    if !(manual || due) || poller.in_flight.is_some() {
+        return;
+    }
+
+    let slot: FetchSlot<R> = Arc::default();
+    poller.in_flight = Some(slot.clone());
+    poller.since_last_fetch = Duration::ZERO;
+
+    let client = client.clone();
+    std::thread::spawn(move || {
+        let result = R::fetch(&client).map_err(|e| e.to_string());
+        if let Ok(mut slot) = slot.lock() {
+            *slot = Some(result);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registering_a_read_twice_keeps_one_poller_with_the_last_settings() {
+        let mut app = App::new();
+        app.register_stylus_read::<SwordCounts>(None);
+        let systems = |app: &mut App| app.get_schedule(Update).unwrap().graph().systems().count();
+        let registered = systems(&mut app);
+        app.register_stylus_read_with_fallback(Some(Duration::from_secs(2)), ReadFallback::Value(SwordCounts(0, 0, 0)));
+
+        assert_eq!(systems(&mut app), registered);
+        let poller = app.world().resource::<ReadPoller<SwordCounts>>();
+        assert_eq!(poller.poll_interval, Some(Duration::from_secs(2)));
+        assert!(poller.fallback.is_some());
+    }
+}
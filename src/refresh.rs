@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use ethers::types::U256;
+use eyre::{Result, WrapErr};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{reads::insert_if_changed, StylusClient, StylusRead, SwordCounts};
+
+/// How long `increment_and_refresh` waits for its write to be mined and confirmed
+pub(crate) const INCREMENT_AND_REFRESH_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Send to increment `color`, wait for confirmation and re-read the counts in the
+/// background. The result arrives as [`IncrementRefreshed`] and updates `SwordCounts`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct IncrementAndRefresh {
+    pub color: u8,
+}
+
+/// Emitted when an [`IncrementAndRefresh`] request finished
+#[derive(Event, Debug, Clone)]
+pub struct IncrementRefreshed {
+    pub color: u8,
+    /// Counts read after the increment confirmed, or which phase failed
+    pub result: Result<SwordCounts, String>,
+}
+
+type RefreshSlot = Arc<Mutex<Option<Result<SwordCounts, String>>>>;
+
+/// `IncrementAndRefresh` requests still running, with the color they increment
+#[derive(Resource, Default)]
+pub(crate) struct IncrementRefreshes(Vec<(u8, RefreshSlot)>);
+
+impl StylusClient {
+    /// Increment `color`, wait until the write is mined and as deep as the confirmation
+    /// policy asks, then read the new counts, blocking the calling thread.
+    ///
+    /// Errors say whether sending, confirming (within two minutes) or reading failed.
+    /// Doesn't touch the `SwordCounts` resource; send [`IncrementAndRefresh`] for that.
+    pub fn increment_and_refresh_blocking(&self, color: u8) -> Result<(u64, u64, u64)> {
+        let SwordCounts(a, b, c) = self.increment_and_read(color)?;
+        Ok((a, b, c))
+    }
+
+    pub(crate) fn increment_and_read(&self, color: u8) -> Result<SwordCounts> {
+        let started = Instant::now();
+        let id = self.increment_sword_async(color);
+        let hash = self
+            .await_submitted(id, INCREMENT_AND_REFRESH_TIMEOUT)
+            .wrap_err("increment_and_refresh: incrementSword was not sent")?;
+        let remaining = INCREMENT_AND_REFRESH_TIMEOUT.saturating_sub(started.elapsed());
+        let receipt = self
+            .await_mined(id, hash, remaining, INCREMENT_AND_REFRESH_TIMEOUT)
+            .wrap_err("increment_and_refresh: incrementSword did not confirm")?;
+        // Increments send no ETH, so only a threshold at 0 asks for extra depth
+        let depth = self.confirmation_policy.depth_for(U256::zero()) as u64;
+        if let Some(block) = receipt.block_number.filter(|_| depth > 0) {
+            let remaining = INCREMENT_AND_REFRESH_TIMEOUT.saturating_sub(started.elapsed());
+            self.wait_for_block(block.as_u64() + depth, remaining)
+                .wrap_err("increment_and_refresh: incrementSword did not confirm")?;
+        }
+        SwordCounts::fetch(self).wrap_err("increment_and_refresh: reading counts failed")
+    }
+}
+
+/// Start a background run per `IncrementAndRefresh` request and report the finished ones
+pub(crate) fn drive_increment_refreshes(
+    mut commands: Commands,
+    client: Option<Res<StylusClient>>,
+    mut running: ResMut<IncrementRefreshes>,
+    mut requests: EventReader<IncrementAndRefresh>,
+    mut refreshed: EventWriter<IncrementRefreshed>,
+) {
+    running.0.retain(|(color, slot)| {
+        let Some(result) = slot.lock().ok().and_then(|mut result| result.take()) else {
+            return true;
+        };
+        if let Ok(counts) = &result {
+            insert_if_changed(&mut commands, *counts);
+        }
+        refreshed.send(IncrementRefreshed { color: *color, result });
+        false
+    });
+
+    for IncrementAndRefresh { color } in requests.read().copied() {
+        let Some(client) = &client else {
+            let result = Err("Contract not initialized".to_string());
+            refreshed.send(IncrementRefreshed { color, result });
+            continue;
+        };
+        let slot: RefreshSlot = Arc::default();
+        running.0.push((color, slot.clone()));
+        let client = StylusClient::clone(client);
+        std::thread::spawn(move || {
+            let result = client.increment_and_read(color).map_err(|e| format!("{:#}", e));
+            if let Ok(mut slot) = slot.lock() {
+                *slot = Some(result);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejected_write_is_reported_as_not_sent() {
+        let error = StylusClient::default().increment_and_refresh_blocking(0).unwrap_err();
+        let message = format!("{:#}", error);
+        assert!(message.contains("incrementSword was not sent"), "{}", message);
+        assert!(message.contains("Contract not initialized"), "{}", message);
+    }
+}
@@ -0,0 +1,240 @@
+use ethers::abi::{encode_packed, Token};
+use ethers::prelude::Signer;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TxHash, U256};
+use ethers::utils::keccak256;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{rate_limit::check_status, StylusClient, StylusSigner};
+
+/// How requests are posted to the relayer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayerFormat {
+    /// The signed intent is the request body, the reply is `{ "txHash": "0x..." }`
+    #[default]
+    Json,
+    /// The signed intent is the only param of a JSON-RPC request, the result is the hash
+    JsonRpc,
+}
+
+/// The `[relayer]` section of `Stylus.toml`: submit writes through a relayer that pays for gas
+#[derive(Debug, Clone, Deserialize)]
+pub struct RelayerConfig {
+    pub url: String,
+    #[serde(default)]
+    pub format: RelayerFormat,
+    /// JSON-RPC method name used with `format = "json_rpc"`
+    #[serde(default = "default_relayer_method")]
+    pub method: String,
+    /// Seconds an intent stays valid after it is signed
+    #[serde(default = "default_intent_ttl_secs")]
+    pub intent_ttl_secs: u64,
+}
+
+fn default_relayer_method() -> String {
+    "relay_sendTransaction".to_string()
+}
+
+fn default_intent_ttl_secs() -> u64 {
+    300
+}
+
+fn unix_time() -> std::time::Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// Intent nonces of the client's signer, shared by clones of the client
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RelayNonce(Arc<Mutex<u64>>);
+
+impl RelayNonce {
+    /// A nonce above every earlier one. Starting from the current time in milliseconds
+    /// keeps nonces increasing across restarts without storing the last one.
+    fn next(&self) -> u64 {
+        let now = unix_time().as_millis() as u64;
+        let mut last = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *last = now.max(*last + 1);
+        *last
+    }
+}
+
+/// A write the player signed for the relayer to submit
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RelayIntent {
+    from: Address,
+    to: Address,
+    data: Bytes,
+    chain_id: u64,
+    /// Increases with every intent of `from`, so a relayer can refuse repeats
+    nonce: u64,
+    /// Unix time in seconds after which the intent must not be submitted
+    deadline: u64,
+    /// EIP-191 signature over `keccak256(abi.encodePacked(chainId, to, data, nonce, deadline))`,
+    /// with `nonce` and `deadline` as `uint256`
+    signature: Bytes,
+}
+
+#[derive(Debug, Serialize)]
+struct RelayRpcRequest<'a> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: [&'a RelayIntent; 1],
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RelayResponse {
+    tx_hash: TxHash,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelayRpcResponse {
+    result: Option<TxHash>,
+    error: Option<serde_json::Value>,
+}
+
+impl StylusClient {
+    /// `incrementSword(color)` submitted through the `[relayer]`, which pays for gas,
+    /// returning the hash the relayer reports. Tracked like any other write.
+    ///
+    /// Fails without a `[relayer]` section rather than falling back to paying for gas.
+    /// With one configured, every write is relayed, so this matches
+    /// [`increment_sword`](Self::increment_sword) apart from the hash and the check.
+    pub fn increment_sword_sponsored(&self, color: u8) -> Result<TxHash> {
+        if self.relayer.is_none() {
+            eyre::bail!("No relayer configured, add a [relayer] section to Stylus.toml");
+        }
+        self.send_contract(self.increment_sword_call(color)?)
+    }
+
+    /// Sign `tx` as an intent and hand it to the relayer, returning the hash it submitted.
+    ///
+    /// The relayer should refuse intents past their deadline, and nonces at or below
+    /// the last one it relayed for the same signer.
+    pub(crate) fn relay_transaction(&self, relayer: &RelayerConfig, signer: &StylusSigner, tx: &TypedTransaction) -> Result<TxHash> {
+        let to = *tx
+            .to_addr()
+            .ok_or_else(|| eyre::eyre!("Relayed transactions need a recipient"))?;
+        let data = tx.data().cloned().unwrap_or_default();
+        let chain_id = self.chain_id().unwrap_or_else(|| signer.signer().chain_id());
+        let nonce = self.relay_nonce.next();
+        let deadline = unix_time().as_secs() + relayer.intent_ttl_secs;
+        let digest = keccak256(encode_packed(&[
+            Token::Uint(U256::from(chain_id)),
+            Token::Address(to),
+            Token::Bytes(data.to_vec()),
+            Token::Uint(U256::from(nonce)),
+            Token::Uint(U256::from(deadline)),
+        ])?);
+
+        let result = self.run_rpc("relayer", async {
+            let signature = signer.signer().sign_message(digest).await?;
+            let intent = RelayIntent {
+                from: signer.address(),
+                to,
+                data,
+                chain_id,
+                nonce,
+                deadline,
+                signature: signature.to_vec().into(),
+            };
+            let request = self.http_client.post(&relayer.url);
+            let tx_hash = match relayer.format {
+                RelayerFormat::Json => {
+                    let response = check_status(request.json(&intent).send().await?)?;
+                    response.json::<RelayResponse>().await?.tx_hash
+                }
+                RelayerFormat::JsonRpc => {
+                    let body = RelayRpcRequest { jsonrpc: "2.0", id: 1, method: &relayer.method, params: [&intent] };
+                    let response = check_status(request.json(&body).send().await?)?;
+                    let response = response.json::<RelayRpcResponse>().await?;
+                    match (response.result, response.error) {
+                        (Some(tx_hash), _) => tx_hash,
+                        (None, error) => eyre::bail!("Relayer rejected the transaction: {}", error.unwrap_or_default()),
+                    }
+                }
+            };
+            Ok(tx_hash)
+        })?;
+        result.map_err(|e| self.log_error("relayer", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{contract_address, MockNode};
+    use ethers::types::{Signature, TransactionRequest};
+    use serde_json::Value;
+    use std::str::FromStr;
+
+    /// Relay two identical increments through a node that records the intents it receives
+    fn relay_twice() -> (Vec<Value>, Address) {
+        let intents = Arc::new(Mutex::new(Vec::new()));
+        let received = intents.clone();
+        let node = MockNode::start(move |method, params| {
+            assert_eq!(method, "relay_sendTransaction");
+            received.lock().unwrap().push(params[0].clone());
+            Ok(Value::String(format!("{:?}", TxHash::repeat_byte(0x11))))
+        });
+        let mut client = node.signing_client();
+        let relayer = RelayerConfig {
+            url: node.url().to_string(),
+            format: RelayerFormat::JsonRpc,
+            method: default_relayer_method(),
+            intent_ttl_secs: 60,
+        };
+        client.relayer = Some(relayer.clone());
+        let signer = client.contract_client.clone().unwrap();
+        let tx: TypedTransaction = TransactionRequest::new().to(contract_address()).data(vec![1, 2, 3]).into();
+        for _ in 0..2 {
+            assert_eq!(client.relay_transaction(&relayer, &signer, &tx).unwrap(), TxHash::repeat_byte(0x11));
+        }
+        let intents = intents.lock().unwrap().clone();
+        (intents, signer.address())
+    }
+
+    #[test]
+    fn repeated_intents_differ_by_nonce() {
+        let (intents, _) = relay_twice();
+        let nonce = |intent: &Value| intent["nonce"].as_u64().unwrap();
+        assert!(nonce(&intents[1]) > nonce(&intents[0]));
+        assert_ne!(intents[0]["signature"], intents[1]["signature"]);
+    }
+
+    #[test]
+    fn intents_sign_their_nonce_and_deadline() {
+        let (intents, from) = relay_twice();
+        let now = unix_time().as_secs();
+        for intent in intents {
+            let deadline = intent["deadline"].as_u64().unwrap();
+            assert!((now + 58..=now + 60).contains(&deadline));
+            let digest = keccak256(encode_packed(&[
+                Token::Uint(U256::from(412346u64)),
+                Token::Address(contract_address()),
+                Token::Bytes(vec![1, 2, 3]),
+                Token::Uint(U256::from(intent["nonce"].as_u64().unwrap())),
+                Token::Uint(U256::from(deadline)),
+            ])
+            .unwrap());
+            let signature = Signature::from_str(intent["signature"].as_str().unwrap()).unwrap();
+            assert_eq!(signature.recover(digest.to_vec()).unwrap(), from);
+            assert_eq!(intent["from"].as_str(), Some(format!("{:?}", from).as_str()));
+        }
+    }
+
+    #[test]
+    fn nonces_keep_increasing_within_a_millisecond() {
+        let nonce = RelayNonce::default();
+        let first = nonce.next();
+        assert!(nonce.next() > first && first >= unix_time().as_millis() as u64 - 1_000);
+    }
+}
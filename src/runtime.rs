@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+use eyre::Result;
+use std::{future::Future, sync::OnceLock};
+use tokio::runtime::{Handle, Runtime};
+
+/// The plugin's own runtime, built on first use and kept for the life of the process
+/// so pooled connections (including prewarmed ones) outlive each call
+static OWNED_RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// Where the client runs its blocking RPC calls.
+///
+/// Uses the app's tokio runtime when a handle was passed to `StylusPlugin`, and
+/// otherwise a single multi-threaded runtime shared by every call.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct StylusRuntime(Option<Handle>);
+
+impl StylusRuntime {
+    pub(crate) fn new(handle: Option<Handle>) -> Self {
+        StylusRuntime(handle)
+    }
+
+    /// Drive `future` to completion from a thread outside the runtime
+    pub(crate) fn block_on<F: Future>(&self, future: F) -> Result<F::Output> {
+        match &self.0 {
+            Some(handle) => Ok(handle.block_on(future)),
+            None => Ok(owned_runtime()?.block_on(future)),
+        }
+    }
+}
+
+/// The shared runtime, building it if this is the first call
+fn owned_runtime() -> Result<&'static Runtime> {
+    if let Some(runtime) = OWNED_RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = Runtime::new()?;
+    // A thread that lost the race keeps the winner's runtime and drops its own
+    Ok(OWNED_RUNTIME.get_or_init(|| runtime))
+}
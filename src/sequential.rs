@@ -0,0 +1,229 @@
+use bevy::prelude::*;
+use ethers::prelude::{Middleware, PendingTransaction, Signer};
+use ethers::types::{BlockNumber, TxHash, U256, U64};
+use eyre::Result;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use crate::{contract::SendOptions, StylusClient, StylusError};
+
+/// How long the worker waits for a receipt before giving up on the rest of the queue
+const SEQUENTIAL_RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Increments sent strictly one after another, in the order they were pushed.
+///
+/// Each write is broadcast only once the previous one was accepted by the node (and,
+/// with `sequential_await_receipt`, mined), with nonces numbered locally so they land
+/// in submission order. A failed write stops the run; the rest of the queue is dropped
+/// so nothing lands out of order.
+///
+/// Higher-priority entries are sent first, FIFO within a priority. Nonces are assigned
+/// when an entry is sent, so reordering queued entries never leaves a gap.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SequentialWriteQueue {
+    /// Queued entries, highest priority first
+    entries: Arc<Mutex<VecDeque<QueuedIncrement>>>,
+    /// Whether a worker thread is currently draining `entries`
+    running: Arc<AtomicBool>,
+    /// Corrections found by the worker, waiting to be sent as events
+    resyncs: Arc<Mutex<Vec<NonceResynced>>>,
+}
+
+/// Emitted when `SequentialWriteQueue`'s local nonce counter was corrected against the chain.
+///
+/// Means transactions the queue counted were dropped (`new < old`), or other writes from
+/// the wallet took nonces in between (`new > old`). Optimistic UI built on earlier
+/// sequential writes may be wrong and should be re-checked.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceResynced {
+    pub old: u64,
+    pub new: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct QueuedIncrement {
+    color: u8,
+    priority: u8,
+}
+
+impl SequentialWriteQueue {
+    /// Request an increment of `color` after every increment pushed before it, at priority 0
+    pub fn push(&mut self, color: u8) {
+        self.push_priority(color, 0);
+    }
+
+    /// Request an increment of `color` ahead of queued entries with a lower `priority`,
+    /// e.g. an urgent move overtaking cosmetic writes. The write already being sent
+    /// is not overtaken.
+    pub fn push_priority(&mut self, color: u8, priority: u8) {
+        if let Ok(mut entries) = self.entries.lock() {
+            let index = entries.partition_point(|entry| entry.priority >= priority);
+            entries.insert(index, QueuedIncrement { color, priority });
+        }
+    }
+
+    /// Increments not yet submitted, excluding the one being sent
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|entries| entries.len()).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn pop(&self) -> Option<u8> {
+        Some(self.entries.lock().ok()?.pop_front()?.color)
+    }
+
+    fn clear(&self) -> usize {
+        self.entries.lock().map(|mut entries| entries.drain(..).count()).unwrap_or_default()
+    }
+}
+
+impl StylusClient {
+    /// The next nonce as counted locally by the sequential queue, next to the wallet's
+    /// pending nonce freshly fetched from the chain, for spotting nonce drift.
+    ///
+    /// The local count is `None` until the sequential queue has sent a write on this
+    /// network. Fails in read-only mode.
+    pub fn nonce_status(&self) -> Result<(Option<u64>, u64)> {
+        if let Some(reason) = &self.read_only_reason {
+            return Err(eyre::eyre!("Client is read-only: {}", reason));
+        }
+        let signer = self.contract_client.as_ref().ok_or(StylusError::NotInitialized)?;
+        let pending = self.block_on_rpc(
+            "eth_getTransactionCount",
+            signer.get_transaction_count(signer.signer().address(), Some(BlockNumber::Pending.into())),
+        )?;
+        let local = self.sequential_nonce.lock().ok().and_then(|nonce| *nonce);
+        Ok((local, pending.as_u64()))
+    }
+
+    /// Adopt the chain's `nonce`, recording a correction on `queue` when it differs from the local count
+    fn resync_sequential_nonce(&self, queue: &SequentialWriteQueue, nonce: U256) {
+        let new = nonce.as_u64();
+        let Ok(mut expected) = self.sequential_nonce.lock() else {
+            return;
+        };
+        if let Some(old) = expected.filter(|old| *old != new) {
+            println!("🔢 Nonce resynced from {} to {}", old, new);
+            if let Ok(mut resyncs) = queue.resyncs.lock() {
+                resyncs.push(NonceResynced { old, new });
+            }
+        }
+        *expected = Some(new);
+    }
+
+    fn advance_sequential_nonce(&self, next: U256) {
+        if let Ok(mut expected) = self.sequential_nonce.lock() {
+            *expected = Some(next.as_u64());
+        }
+    }
+
+    /// Send one queued increment with `nonce`, waiting for its receipt when configured
+    fn send_sequential(&self, color: u8, nonce: Option<U256>) -> Result<TxHash> {
+        let mut tx = self.increment_sword_call(color)?.tx;
+        if let Some(nonce) = nonce {
+            tx.set_nonce(nonce);
+        }
+        // A deduplicated write would not use its nonce and leave a gap
+        let options = SendOptions { skip_dedup: true, ..Default::default() };
+        let tx_hash = self.send_transaction_with("incrementSword", tx, options)?;
+        if self.sequential_await_receipt {
+            let provider = self.write_provider.as_ref().ok_or(StylusError::NotInitialized)?;
+            let pending = PendingTransaction::new(tx_hash, provider).interval(self.confirmation_poll_interval);
+            let receipt = self
+                .runtime
+                .block_on(async { tokio::time::timeout(SEQUENTIAL_RECEIPT_TIMEOUT, pending).await })?
+                .map_err(|_| StylusError::Timeout {
+                    method: "sequential_await_receipt".to_string(),
+                    timeout: SEQUENTIAL_RECEIPT_TIMEOUT,
+                })??;
+            match receipt {
+                Some(receipt) if receipt.status == Some(U64::from(1)) => {}
+                Some(_) => return Err(eyre::eyre!("Transaction {:?} reverted", tx_hash)),
+                None => return Err(eyre::eyre!("Transaction {:?} dropped from mempool", tx_hash)),
+            }
+        }
+        Ok(tx_hash)
+    }
+
+    /// The wallet's next nonce including pending transactions, `None` when a relayer sends for us
+    fn next_sequential_nonce(&self) -> Result<Option<U256>> {
+        if self.relayer.is_some() {
+            return Ok(None);
+        }
+        let signer = self.contract_client.as_ref().ok_or(StylusError::NotInitialized)?;
+        let nonce = self.block_on_rpc(
+            "eth_getTransactionCount",
+            signer.get_transaction_count(signer.signer().address(), Some(BlockNumber::Pending.into())),
+        )?;
+        Ok(Some(nonce))
+    }
+
+    /// Drain `queue` in order on the calling thread, stopping at the first failure
+    fn run_sequential_writes(&self, queue: &SequentialWriteQueue) {
+        let mut nonce = match self.next_sequential_nonce() {
+            Ok(nonce) => {
+                if let Some(nonce) = nonce {
+                    self.resync_sequential_nonce(queue, nonce);
+                }
+                nonce
+            }
+            Err(e) => {
+                println!("⚠️ Failed to fetch nonce, dropping {} sequential increments: {}", queue.clear(), e);
+                return;
+            }
+        };
+        while let Some(color) = queue.pop() {
+            match self.send_sequential(color, nonce) {
+                Ok(tx_hash) => {
+                    println!("🔢 Sequential increment {} sent: {:?}", color, tx_hash);
+                    if let Some(sent) = nonce {
+                        nonce = Some(sent + 1);
+                        self.advance_sequential_nonce(sent + 1);
+                    }
+                }
+                Err(e) => {
+                    let dropped = queue.clear();
+                    println!("⚠️ Sequential increment {} failed, dropping {} queued after it: {}", color, dropped, e);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Start a worker for `SequentialWriteQueue` when it has work and none is running,
+/// and announce the nonce corrections it found
+pub(crate) fn drive_sequential_writes(
+    client: Option<Res<StylusClient>>,
+    queue: Res<SequentialWriteQueue>,
+    mut resynced: EventWriter<NonceResynced>,
+) {
+    if let Ok(mut resyncs) = queue.resyncs.lock() {
+        resynced.send_batch(resyncs.drain(..));
+    }
+    let Some(client) = client else {
+        return;
+    };
+    if queue.is_empty() || queue.running.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let client = client.clone();
+    let queue = queue.clone();
+    std::thread::spawn(move || loop {
+        client.run_sequential_writes(&queue);
+        queue.running.store(false, Ordering::SeqCst);
+        // A push may have landed between the last pop and clearing the flag
+        if queue.is_empty() || queue.running.swap(true, Ordering::SeqCst) {
+            break;
+        }
+    });
+}
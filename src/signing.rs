@@ -0,0 +1,86 @@
+use ethers::types::{Address, RecoveryMessage, Signature};
+use ethers::utils::{hash_message, keccak256};
+use eyre::Result;
+
+use crate::StylusClient;
+
+/// How a message was hashed before signing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// `personal_sign` semantics: the message is prefixed with
+    /// `"\x19Ethereum Signed Message:\n" + len` before hashing, as wallets do
+    #[default]
+    Eip191,
+    /// Plain `keccak256(message)`, without a prefix
+    Raw,
+}
+
+impl StylusClient {
+    /// Sign `message` with the client's wallet using `personal_sign` (EIP-191) semantics,
+    /// e.g. as a login or ownership proof. No RPC is made.
+    pub fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        let signer = self
+            .contract_client
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Signer not initialized"))?;
+        Ok(signer.signer().sign_hash(hash_message(message))?)
+    }
+
+    /// Whether `signature` over `message` was made by `expected`.
+    ///
+    /// Pure cryptography, so it works without a provider. Fails only if the
+    /// signature is malformed; a valid signature by another address gives `false`.
+    pub fn verify_message(
+        &self,
+        message: &[u8],
+        signature: &Signature,
+        expected: Address,
+        format: MessageFormat,
+    ) -> Result<bool> {
+        let recovery = match format {
+            MessageFormat::Eip191 => RecoveryMessage::Data(message.to_vec()),
+            MessageFormat::Raw => RecoveryMessage::Hash(keccak256(message).into()),
+        };
+        let signer = signature
+            .recover(recovery)
+            .map_err(|e| eyre::eyre!("Invalid signature: {}", e))?;
+        Ok(signer == expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MockNode;
+    use std::str::FromStr;
+
+    /// A client holding the dev wallet; signing and verifying make no RPC calls
+    fn signing_client() -> (StylusClient, Address) {
+        let client = MockNode::start(|method, _| panic!("unexpected {}", method)).signing_client();
+        let address = client.contract_client.as_ref().unwrap().address();
+        (client, address)
+    }
+
+    #[test]
+    fn signature_round_trips_through_its_string_form() {
+        let (client, address) = signing_client();
+        let signature = client.sign_message(b"login:42").unwrap();
+        let parsed = Signature::from_str(&signature.to_string()).unwrap();
+        assert_eq!(parsed, signature);
+        assert!(client.verify_message(b"login:42", &parsed, address, MessageFormat::Eip191).unwrap());
+    }
+
+    #[test]
+    fn verify_rejects_other_signers_messages_and_formats() {
+        let (client, address) = signing_client();
+        let signature = client.sign_message(b"login:42").unwrap();
+        assert!(!client.verify_message(b"login:42", &signature, Address::repeat_byte(1), MessageFormat::Eip191).unwrap());
+        assert!(!client.verify_message(b"login:43", &signature, address, MessageFormat::Eip191).unwrap());
+        assert!(!client.verify_message(b"login:42", &signature, address, MessageFormat::Raw).unwrap());
+    }
+
+    #[test]
+    fn sign_fails_without_wallet() {
+        assert!(StylusClient::default().sign_message(b"login:42").is_err());
+    }
+}
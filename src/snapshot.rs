@@ -0,0 +1,198 @@
+use ethers::abi::Token;
+use ethers::contract::Contract;
+use ethers::types::I256;
+use eyre::Result;
+use std::collections::BTreeMap;
+
+use crate::StylusClient;
+
+/// Outputs of a set of view functions captured at one point in time, see [`StylusClient::snapshot`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateSnapshot {
+    /// Output tokens of each snapshotted function, by name
+    pub values: BTreeMap<String, Vec<Token>>,
+}
+
+/// A view output whose value differs between two snapshots
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    pub function: String,
+    /// Position of the output in the function's return values
+    pub output: usize,
+    pub before: Token,
+    pub after: Token,
+    /// `after - before` for integer outputs, `None` for other types or if it overflows
+    pub delta: Option<I256>,
+}
+
+impl StateSnapshot {
+    /// Outputs that changed between `self` and the later snapshot `after`.
+    /// Functions present in only one of the snapshots are ignored.
+    pub fn diff(&self, after: &StateSnapshot) -> Vec<ValueChange> {
+        let mut changes = Vec::new();
+        for (function, before_values) in &self.values {
+            let Some(after_values) = after.values.get(function) else {
+                continue;
+            };
+            for (output, (before, after)) in before_values.iter().zip(after_values).enumerate() {
+                if before != after {
+                    changes.push(ValueChange {
+                        function: function.clone(),
+                        output,
+                        before: before.clone(),
+                        after: after.clone(),
+                        delta: integer_delta(before, after),
+                    });
+                }
+            }
+        }
+        changes
+    }
+}
+
+fn integer_delta(before: &Token, after: &Token) -> Option<I256> {
+    match (before, after) {
+        (Token::Int(before), Token::Int(after)) => I256::from_raw(*after).checked_sub(I256::from_raw(*before)),
+        (Token::Uint(before), Token::Uint(after)) => {
+            I256::try_from(*after).ok()?.checked_sub(I256::try_from(*before).ok()?)
+        }
+        _ => None,
+    }
+}
+
+impl StylusClient {
+    /// Capture the outputs of argument-less view functions, e.g. before and after an action.
+    ///
+    /// All functions are read in one Multicall3 request at the configured read block
+    /// tag, or one by one when no multicall contract is deployed.
+    pub fn snapshot(&self, functions: &[&str]) -> Result<StateSnapshot> {
+        let Some(mut multicall) = self.multicall()? else {
+            let values = functions
+                .iter()
+                .map(|function| Ok((function.to_string(), self.call_view(function, &[])?)))
+                .collect::<Result<_>>()?;
+            return Ok(StateSnapshot { values });
+        };
+        let (Some(provider), Some(address)) = (&self.provider, self.contract_address) else {
+            return Err(eyre::eyre!("Contract not initialized"));
+        };
+
+        let contract = Contract::new(address, self.abi().clone(), provider.clone());
+        for function in functions {
+            multicall.add_call(contract.method::<_, Token>(function, ())?, false);
+        }
+        let results = self.with_read_block(|block| {
+            let mut multicall = multicall.clone();
+            multicall.block = block;
+            self.block_on_rpc("multicall", multicall.call_raw())
+        })?;
+
+        let mut values = BTreeMap::new();
+        for (function, result) in functions.iter().zip(results) {
+            let token = result.map_err(|data| eyre::eyre!("{} reverted with data {}", function, data))?;
+            // Multicall wraps functions with several outputs in a tuple
+            let outputs = match token {
+                Token::Tuple(tokens) if self.abi().function(function)?.outputs.len() != 1 => tokens,
+                token => vec![token],
+            };
+            values.insert(function.to_string(), outputs);
+        }
+        Ok(StateSnapshot { values })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{aggregate3, call_data, encoded, MockNode};
+    use crate::BLOCKCHAINCONTRACT_ABI;
+    use ethers::prelude::MULTICALL_ADDRESS;
+    use ethers::types::{Address, U256};
+    use serde_json::Value;
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    };
+
+    fn snapshot(red: u64, owner: Address) -> StateSnapshot {
+        StateSnapshot {
+            values: BTreeMap::from([
+                ("getSwordCounts".to_string(), vec![Token::Uint(red.into()), Token::Uint(2.into()), Token::Uint(3.into())]),
+                ("owner".to_string(), vec![Token::Address(owner)]),
+            ]),
+        }
+    }
+
+    /// A node whose `getSwordCounts` reports `red` red swords, with or without Multicall3
+    fn sword_node(multicall_deployed: bool, red: Arc<AtomicU64>) -> MockNode {
+        let counts = move || {
+            let red = red.load(Ordering::SeqCst);
+            vec![Token::Uint(red.into()), Token::Uint(2.into()), Token::Uint(3.into())]
+        };
+        MockNode::start(move |method, params| match method {
+            "eth_getCode" => Ok(Value::String(if multicall_deployed { "0x6080" } else { "0x" }.to_string())),
+            "eth_chainId" => Ok(Value::String("0x64aba".to_string())),
+            _ => {
+                let (selector, args) = call_data(params);
+                let to = params[0]["to"].as_str().unwrap_or_default();
+                if to == format!("{:?}", MULTICALL_ADDRESS) {
+                    return Ok(aggregate3(&args, |_| counts()));
+                }
+                assert!(!multicall_deployed, "called the contract directly despite multicall");
+                assert_eq!(selector, BLOCKCHAINCONTRACT_ABI.function("getSwordCounts").unwrap().short_signature());
+                Ok(encoded(&counts()))
+            }
+        })
+    }
+
+    fn snapshot_action(multicall_deployed: bool) {
+        let red = Arc::new(AtomicU64::new(1));
+        let client = sword_node(multicall_deployed, red.clone()).client();
+        let before = client.snapshot(&["getSwordCounts"]).unwrap();
+        let counts = [Token::Uint(1.into()), Token::Uint(2.into()), Token::Uint(3.into())];
+        assert_eq!(before.values, BTreeMap::from([("getSwordCounts".to_string(), counts.to_vec())]));
+
+        red.store(3, Ordering::SeqCst);
+        let after = client.snapshot(&["getSwordCounts"]).unwrap();
+        let changes = before.diff(&after);
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].output, changes[0].delta), (0, Some(I256::from(2))));
+    }
+
+    #[test]
+    fn snapshot_reads_through_multicall() {
+        snapshot_action(true);
+    }
+
+    #[test]
+    fn snapshot_falls_back_to_sequential_calls() {
+        snapshot_action(false);
+    }
+
+    #[test]
+    fn snapshot_rejects_unknown_functions() {
+        let client = sword_node(false, Arc::default()).client();
+        assert!(client.snapshot(&["getSwordCount"]).is_err());
+    }
+
+    #[test]
+    fn diff_reports_changed_outputs_with_integer_deltas() {
+        let before = snapshot(5, Address::repeat_byte(1));
+        let after = snapshot(3, Address::repeat_byte(2));
+        let changes = before.diff(&after);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].function, "getSwordCounts");
+        assert_eq!(changes[0].output, 0);
+        assert_eq!(changes[0].delta, Some(I256::from(-2)));
+        assert_eq!(changes[1].function, "owner");
+        assert_eq!(changes[1].delta, None);
+    }
+
+    #[test]
+    fn diff_skips_functions_missing_from_either_side() {
+        let before = snapshot(1, Address::zero());
+        let mut after = StateSnapshot::default();
+        after.values.insert("totalSupply".to_string(), vec![Token::Uint(U256::one())]);
+        assert!(before.diff(&after).is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+use ethers::types::{TransactionReceipt, U256};
+use ethers::utils::format_ether;
+use std::collections::VecDeque;
+
+use crate::{NetworkSwitched, StylusClient, TxConfirmed};
+
+/// Gas paid for writes confirmed this session, from their receipts
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionSpend {
+    /// Total of `gas_used * effective_gas_price`, in wei
+    pub cost_wei: U256,
+    /// Number of confirmed writes counted
+    pub transactions: u64,
+}
+
+impl SessionSpend {
+    /// Start counting from zero again
+    pub fn reset(&mut self) {
+        *self = SessionSpend::default();
+    }
+
+    /// The total in ether for display, e.g. `0.000210000000000000`
+    pub fn cost_ether(&self) -> String {
+        format_ether(self.cost_wei)
+    }
+
+    fn add(&mut self, receipt: &TransactionReceipt) {
+        let gas_used = receipt.gas_used.unwrap_or_default();
+        let gas_price = receipt.effective_gas_price.unwrap_or_default();
+        self.cost_wei = self.cost_wei.saturating_add(gas_used.saturating_mul(gas_price));
+        self.transactions += 1;
+    }
+}
+
+/// Samples `GasStats` keeps unless `gas_stats_window` says otherwise
+pub(crate) const DEFAULT_GAS_STATS_WINDOW: usize = 50;
+
+/// Rolling gas usage of recently confirmed writes, e.g. for tuning gas limits
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct GasStats {
+    /// `gas_used` of the most recent confirmed writes, oldest first
+    pub samples: VecDeque<u64>,
+    /// Mean of `samples`, 0 before the first confirmation
+    pub average: u64,
+}
+
+impl GasStats {
+    /// Add a sample, keeping at most `window` of them
+    fn record(&mut self, gas_used: u64, window: usize) {
+        self.samples.push_back(gas_used);
+        while self.samples.len() > window.max(1) {
+            self.samples.pop_front();
+        }
+        let total: u128 = self.samples.iter().map(|gas| u128::from(*gas)).sum();
+        self.average = (total / self.samples.len() as u128) as u64;
+    }
+}
+
+/// Feed the `gas_used` of each confirmed write into `GasStats`
+pub(crate) fn track_gas_stats(
+    client: Option<Res<StylusClient>>,
+    mut stats: ResMut<GasStats>,
+    mut confirmed: EventReader<TxConfirmed>,
+) {
+    let window = client.map_or(DEFAULT_GAS_STATS_WINDOW, |client| client.gas_stats_window);
+    for TxConfirmed { receipt, .. } in confirmed.read() {
+        if let Some(gas_used) = receipt.gas_used {
+            stats.record(gas_used.low_u64(), window);
+        }
+    }
+}
+
+/// Add each confirmed write's cost to `SessionSpend`, resetting it on network switches if configured
+pub(crate) fn track_session_spend(
+    client: Option<Res<StylusClient>>,
+    mut spend: ResMut<SessionSpend>,
+    mut confirmed: EventReader<TxConfirmed>,
+    mut switched: EventReader<NetworkSwitched>,
+) {
+    let switches = switched.read().count();
+    if switches > 0 && client.is_some_and(|client| client.reset_session_spend_on_switch) {
+        spend.reset();
+    }
+    for TxConfirmed { receipt, .. } in confirmed.read() {
+        spend.add(receipt);
+    }
+}
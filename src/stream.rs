@@ -0,0 +1,47 @@
+use ethers::types::TxHash;
+use tokio::sync::broadcast;
+
+use crate::{StylusClient, SwordIncremented, TxCancelled, TxConfirmed, TxFailed};
+
+/// Everything the plugin reports, for consumers outside the ECS
+#[derive(Debug, Clone)]
+pub enum StylusEvent {
+    /// A write was broadcast and is now tracked until mined
+    Submitted { hash: TxHash, function: String },
+    Confirmed(Box<TxConfirmed>),
+    Failed(TxFailed),
+    Cancelled(TxCancelled),
+    /// A contract log picked up by the event poller
+    Log(SwordIncremented),
+}
+
+/// Fan-out of [`StylusEvent`]s, disabled unless `event_channel_capacity` is set
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EventStream(Option<broadcast::Sender<StylusEvent>>);
+
+impl EventStream {
+    pub(crate) fn new(capacity: Option<usize>) -> Self {
+        EventStream(capacity.map(|capacity| broadcast::channel(capacity.max(1)).0))
+    }
+
+    /// Hand `event` to every subscriber; never blocks, and is a no-op without subscribers
+    pub(crate) fn publish(&self, event: StylusEvent) {
+        if let Some(sender) = &self.0 {
+            let _ = sender.send(event);
+        }
+    }
+}
+
+impl StylusClient {
+    /// Receive every [`StylusEvent`] from now on, e.g. on a networking thread that
+    /// can't read Bevy events. `None` unless `event_channel_capacity` is configured.
+    ///
+    /// Events are published as the plugin's systems report them, so they arrive in the
+    /// same frame as the matching Bevy events. The channel holds `event_channel_capacity`
+    /// events per receiver: the game never waits for a slow receiver, which instead
+    /// loses the oldest events and gets `RecvError::Lagged(skipped)` on its next `recv`.
+    /// Use `blocking_recv` outside a tokio runtime.
+    pub fn subscribe_events(&self) -> Option<broadcast::Receiver<StylusEvent>> {
+        self.event_stream.0.as_ref().map(|sender| sender.subscribe())
+    }
+}
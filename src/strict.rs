@@ -0,0 +1,189 @@
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use toml::{Table, Value};
+
+use crate::{
+    networks::NetworkConfig, ConfirmationThresholdConfig, ContractConfig, DeploymentConfig, FunctionsConfig, OptionsConfig,
+    RelayerConfig, StylusConfig,
+};
+
+/// Serialized field names of `T`, as serde passes them to `deserialize_struct`
+fn field_names<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    struct Introspect<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for Introspect<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("field names captured"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+            option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(Introspect(&mut fields));
+    fields
+}
+
+/// Record the keys of `table` that aren't in `fields`, as `prefix.key`
+fn collect_unknown(table: &Table, fields: &[&str], prefix: &str, unknown: &mut Vec<String>) {
+    for key in table.keys().filter(|key| !fields.contains(&key.as_str())) {
+        unknown.push(if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) });
+    }
+}
+
+/// First required `[deployment]` / `[functions]` entry absent from `content`, as a dotted
+/// path, or the section name when the whole section is missing. Every field of those
+/// sections is required, so this catches what serde would report without a location.
+pub(crate) fn missing_section_field(content: &str) -> Result<Option<String>, toml::de::Error> {
+    let root: Table = toml::from_str(content)?;
+    let sections = [
+        ("deployment", field_names::<DeploymentConfig>()),
+        ("functions", field_names::<FunctionsConfig>()),
+    ];
+    for (section, fields) in sections {
+        let Some(Value::Table(table)) = root.get(section) else {
+            return Ok(Some(section.to_string()));
+        };
+        if let Some(field) = fields.iter().find(|field| !table.contains_key(**field)) {
+            return Ok(Some(format!("{}.{}", section, field)));
+        }
+    }
+    Ok(None)
+}
+
+/// Keys in `content` that no known section or option reads, e.g. a misspelled `rpc_ur`
+pub(crate) fn unknown_keys(content: &str) -> Result<Vec<String>, toml::de::Error> {
+    let root: Table = toml::from_str(content)?;
+    let mut unknown = Vec::new();
+    collect_unknown(&root, field_names::<StylusConfig>(), "", &mut unknown);
+
+    let sections = [
+        ("contract", field_names::<ContractConfig>()),
+        ("deployment", field_names::<DeploymentConfig>()),
+        ("functions", field_names::<FunctionsConfig>()),
+        ("options", field_names::<OptionsConfig>()),
+        ("relayer", field_names::<RelayerConfig>()),
+    ];
+    for (section, fields) in sections {
+        if let Some(Value::Table(table)) = root.get(section) {
+            collect_unknown(table, fields, section, &mut unknown);
+        }
+    }
+
+    let networks = root.get("networks").and_then(Value::as_table);
+    for (name, network) in networks.into_iter().flatten() {
+        if let Some(table) = network.as_table() {
+            collect_unknown(table, field_names::<NetworkConfig>(), &format!("networks.{}", name), &mut unknown);
+        }
+    }
+
+    let thresholds = root
+        .get("options")
+        .and_then(|options| options.get("confirmation_thresholds"))
+        .and_then(Value::as_array);
+    for (index, threshold) in thresholds.into_iter().flatten().enumerate() {
+        if let Some(table) = threshold.as_table() {
+            let prefix = format!("options.confirmation_thresholds[{}]", index);
+            collect_unknown(table, field_names::<ConfirmationThresholdConfig>(), &prefix, &mut unknown);
+        }
+    }
+    Ok(unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[contract]
+address = "0x0000000000000000000000000000000000000001"
+network = "nitro-devnode"
+rpc_url = "http://localhost:8547"
+
+[deployment]
+tx_hash = "0x1"
+activation_tx_hash = "0x2"
+contract_size = "1 KB"
+wasm_size = "4 KB"
+wasm_data_fee = "0.0001 ETH"
+
+[functions]
+signatures = ["function incrementSword(uint256 color) external"]
+"#;
+
+    #[test]
+    fn known_keys_pass() {
+        let content = format!("{}\n[options]\nstrict_config = true\n", CONFIG);
+        assert_eq!(unknown_keys(&content).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unknown_keys_are_reported_by_path() {
+        // Top-level keys go before the first table
+        let content = "colour = \"red\"\n".to_string()
+            + &CONFIG.replace("rpc_url =", "rpc_ur =")
+            + "\n[options]\n[[options.confirmation_thresholds]]\nmin_value_ether = \"1\"\ndepth = 3\nblocks = 2\n"
+            + "\n[networks.sepolia]\nrpc_url = \"https://sepolia\"\nchainid = 1\n";
+        let mut unknown = unknown_keys(&content).unwrap();
+        unknown.sort();
+        assert_eq!(
+            unknown,
+            ["colour", "contract.rpc_ur", "networks.sepolia.chainid", "options.confirmation_thresholds[0].blocks"]
+        );
+    }
+
+    #[test]
+    fn strict_config_rejects_unknown_keys() {
+        let content = format!("{}\n[options]\nstrict_config = true\nprewarn = false\n", CONFIG);
+        let error = crate::parse_stylus_config(&content, "Stylus.toml").unwrap_err();
+        assert_eq!(error.to_string(), "Unknown keys in Stylus.toml: options.prewarn");
+
+        let lenient = content.replace("strict_config = true", "strict_config = false");
+        assert!(crate::parse_stylus_config(&lenient, "Stylus.toml").is_ok());
+    }
+
+    #[test]
+    fn complete_sections_have_nothing_missing() {
+        assert_eq!(missing_section_field(CONFIG).unwrap(), None);
+    }
+
+    #[test]
+    fn missing_sections_and_fields_are_named() {
+        let without_deployment = CONFIG.replace("[deployment]", "[deployment_old]");
+        assert_eq!(missing_section_field(&without_deployment).unwrap().as_deref(), Some("deployment"));
+        let without_signatures = CONFIG.replace("signatures =", "signature =");
+        assert_eq!(missing_section_field(&without_signatures).unwrap().as_deref(), Some("functions.signatures"));
+        let without_fee = CONFIG.replace("wasm_data_fee = \"0.0001 ETH\"\n", "");
+        assert_eq!(missing_section_field(&without_fee).unwrap().as_deref(), Some("deployment.wasm_data_fee"));
+        assert!(missing_section_field("not = [toml").is_err());
+    }
+
+    #[test]
+    fn parsing_reports_the_missing_entry_by_path() {
+        let error = |content: &str| crate::parse_stylus_config(content, "Stylus.toml").unwrap_err().to_string();
+        assert_eq!(
+            error(&CONFIG.replace("activation_tx_hash", "activation")),
+            "Stylus.toml is missing `deployment.activation_tx_hash`"
+        );
+        assert_eq!(
+            error(&CONFIG.replace("address = \"0x0000000000000000000000000000000000000001\"\n", "")),
+            "Stylus.toml is missing `contract.address`"
+        );
+        assert_eq!(error(&CONFIG.replace("rpc_url = ", "rpc = ")), "Stylus.toml is missing `contract.rpc_url`");
+        assert!(crate::parse_stylus_config(CONFIG, "Stylus.toml").is_ok());
+    }
+}
@@ -0,0 +1,141 @@
+//! A stand-in JSON-RPC node for tests of client methods.
+
+use ethers::abi::{self, ParamType, Token};
+use ethers::providers::JsonRpcError;
+use ethers::prelude::{LocalWallet, Signer, SignerMiddleware};
+use ethers::types::Address;
+use ethers::utils::hex;
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    str::FromStr,
+    sync::Arc,
+};
+
+use crate::{BlockchainContract, StylusClient};
+
+/// First account of the Nitro dev node, never funded outside it
+pub(crate) const DEV_KEY: &str = "0xb6b15c8cb491557369f3c7d2c287b053eb229daa9c22138887752191c9520659";
+
+type Handler = dyn Fn(&str, &Value) -> Result<Value, JsonRpcError> + Send + Sync;
+
+/// Address the mock node's client treats as the game contract
+pub(crate) fn contract_address() -> Address {
+    Address::repeat_byte(0xcc)
+}
+
+/// Answers each request with `handler(method, params)`, on a background thread
+pub(crate) struct MockNode {
+    url: String,
+}
+
+impl MockNode {
+    pub(crate) fn start(handler: impl Fn(&str, &Value) -> Result<Value, JsonRpcError> + Send + Sync + 'static) -> Self {
+        let handler: Arc<Handler> = Arc::new(handler);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+                let mut body = vec![0; length];
+                if reader.read_exact(&mut body).is_err() {
+                    continue;
+                }
+                let request: Value = serde_json::from_slice(&body).unwrap_or_default();
+                let method = request["method"].as_str().unwrap_or_default().to_string();
+                let reply = match handler(&method, &request["params"]) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+                    Err(JsonRpcError { code, message, data }) => json!({
+                        "jsonrpc": "2.0",
+                        "id": request["id"],
+                        "error": { "code": code, "message": message, "data": data },
+                    }),
+                };
+                let reply = reply.to_string();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    reply.len(),
+                    reply
+                );
+            }
+        });
+        MockNode { url }
+    }
+
+    /// Where the node listens, e.g. for a `Stylus.toml` `rpc_url`
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// A read-only client connected to the node, with [`contract_address`] as the contract
+    pub(crate) fn client(&self) -> StylusClient {
+        let mut client = StylusClient::default();
+        let provider = Arc::new(client.http_provider(&self.url).unwrap());
+        client.read_contract = Some(BlockchainContract::new(contract_address(), provider.clone()));
+        client.contract_address = Some(contract_address());
+        client.multicall_address = Some(ethers::prelude::MULTICALL_ADDRESS);
+        client.write_provider = Some(provider.clone());
+        client.provider = Some(provider);
+        client
+    }
+
+    /// [`client`](Self::client) with a wallet attached, for chain id 412346 (the Nitro dev node)
+    pub(crate) fn signing_client(&self) -> StylusClient {
+        let mut client = self.client();
+        let wallet = LocalWallet::from_str(DEV_KEY).unwrap().with_chain_id(412346u64);
+        let provider = client.write_provider.clone().unwrap();
+        let signer = Arc::new(SignerMiddleware::new((*provider).clone(), wallet));
+        client.contract = Some(BlockchainContract::new(contract_address(), signer.clone()));
+        client.contract_client = Some(signer);
+        client.chain_id = Some(412346);
+        client
+    }
+}
+
+/// ABI-encoded `tokens` as the hex string an `eth_call` returns
+pub(crate) fn encoded(tokens: &[Token]) -> Value {
+    Value::String(format!("0x{}", hex::encode(abi::encode(tokens))))
+}
+
+/// The call data of an `eth_call`, split into its selector and arguments
+pub(crate) fn call_data(params: &Value) -> ([u8; 4], Vec<u8>) {
+    let call = &params[0];
+    let data = call["data"].as_str().or(call["input"].as_str()).unwrap_or_default();
+    let bytes = hex::decode(data.trim_start_matches("0x")).unwrap_or_default();
+    match bytes.get(..4).and_then(|selector| <[u8; 4]>::try_from(selector).ok()) {
+        Some(selector) => (selector, bytes[4..].to_vec()),
+        None => ([0; 4], Vec::new()),
+    }
+}
+
+/// Answer a Multicall3 `aggregate3` call by running each inner call's data through `answer`
+pub(crate) fn aggregate3(args: &[u8], answer: impl Fn(&[u8]) -> Vec<Token>) -> Value {
+    let call = ParamType::Tuple(vec![ParamType::Address, ParamType::Bool, ParamType::Bytes]);
+    let calls = abi::decode(&[ParamType::Array(Box::new(call))], args).unwrap();
+    let results = calls[0]
+        .clone()
+        .into_array()
+        .unwrap()
+        .into_iter()
+        .map(|call| {
+            let data = call.into_tuple().unwrap()[2].clone().into_bytes().unwrap();
+            Token::Tuple(vec![Token::Bool(true), Token::Bytes(abi::encode(&answer(&data)))])
+        })
+        .collect();
+    encoded(&[Token::Array(results)])
+}
@@ -0,0 +1,53 @@
+use ethers::prelude::abigen;
+use ethers::types::{Address, TxHash, U256};
+use eyre::Result;
+
+use crate::{ContractConfig, StylusClient, StylusError};
+
+abigen!(
+    Erc20,
+    r#"[
+        function balanceOf(address owner) external view returns (uint256)
+        function approve(address spender, uint256 amount) external returns (bool)
+        function transfer(address to, uint256 amount) external returns (bool)
+    ]"#
+);
+
+impl ContractConfig {
+    /// The configured `token_address`, `None` when the game has no token
+    pub(crate) fn token_address(&self) -> Result<Option<Address>> {
+        self.token_address
+            .as_ref()
+            .map(|address| address.parse().map_err(|e| eyre::eyre!("Invalid token_address {}: {}", address, e)))
+            .transpose()
+    }
+}
+
+impl StylusClient {
+    fn token(&self) -> Result<Address> {
+        self.token_address
+            .ok_or_else(|| eyre::eyre!("No token_address configured in the [contract] section"))
+    }
+
+    /// Token balance of `owner` in the token's smallest unit, at the configured read block tag
+    pub fn token_balance(&self, owner: Address) -> Result<U256> {
+        let token = self.token()?;
+        let provider = self.provider.as_ref().ok_or(StylusError::NotInitialized)?;
+        self.call_contract(Erc20::new(token, provider.clone()).balance_of(owner))
+    }
+
+    /// Let `spender` move up to `amount` of the wallet's tokens, e.g. the game contract
+    /// before it takes a forging fee
+    pub fn token_approve(&self, spender: Address, amount: U256) -> Result<TxHash> {
+        let token = self.token()?;
+        let signer = self.contract_client.as_ref().ok_or(StylusError::NotInitialized)?;
+        self.send_contract(Erc20::new(token, signer.clone()).approve(spender, amount))
+    }
+
+    /// Send `amount` of the wallet's tokens to `to`
+    pub fn token_transfer(&self, to: Address, amount: U256) -> Result<TxHash> {
+        let token = self.token()?;
+        let signer = self.contract_client.as_ref().ok_or(StylusError::NotInitialized)?;
+        self.send_contract(Erc20::new(token, signer.clone()).transfer(to, amount))
+    }
+}
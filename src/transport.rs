@@ -0,0 +1,296 @@
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use reqwest::{header::CONTENT_TYPE, StatusCode, Url};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::rate_limit::retry_after_of;
+
+/// Wait before retrying a 429 that came without `Retry-After`, doubled on each retry
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// How [`StylusHttp`] retries requests the endpoint answered with HTTP 429
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RateLimitRetry {
+    /// Retries after the first attempt before the rate limit is reported
+    pub max_retries: u32,
+    /// Longest delay waited for; an endpoint asking for more fails the call right away
+    pub max_wait: Duration,
+}
+
+impl Default for RateLimitRetry {
+    fn default() -> Self {
+        RateLimitRetry { max_retries: 3, max_wait: Duration::from_secs(10) }
+    }
+}
+
+/// JSON-RPC transport over HTTP that, unlike ethers' `Http`, reads the response status.
+///
+/// A 429 is retried after the delay from its `Retry-After` header, as often and for
+/// as long as the `rate_limit_*` options allow. When the endpoint keeps refusing, the
+/// call fails with a JSON-RPC error of code 429 carrying the delay, which the client
+/// turns into `StylusError::RateLimited`.
+#[derive(Debug)]
+pub struct StylusHttp {
+    id: AtomicU64,
+    client: reqwest::Client,
+    url: Url,
+    retry: RateLimitRetry,
+}
+
+impl Clone for StylusHttp {
+    fn clone(&self) -> Self {
+        StylusHttp::new(self.url.clone(), self.client.clone(), self.retry)
+    }
+}
+
+/// Errors returned by the [`StylusHttp`] transport
+#[derive(Debug)]
+pub enum StylusHttpError {
+    /// The request could not be sent or its response read
+    Http(reqwest::Error),
+    /// The endpoint answered with a JSON-RPC error, including the code 429 error
+    /// reported once rate-limit retries ran out
+    JsonRpc(JsonRpcError),
+    /// The request or response could not be (de)serialized
+    Serde { err: serde_json::Error, text: String },
+}
+
+impl fmt::Display for StylusHttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StylusHttpError::Http(e) => write!(f, "{}", e),
+            StylusHttpError::JsonRpc(e) => write!(f, "{}", e),
+            StylusHttpError::Serde { err, text } => write!(f, "Deserialization error: {}. Response: {}", err, text),
+        }
+    }
+}
+
+impl std::error::Error for StylusHttpError {}
+
+impl RpcError for StylusHttpError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            StylusHttpError::JsonRpc(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            StylusHttpError::Serde { err, .. } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<StylusHttpError> for ProviderError {
+    fn from(src: StylusHttpError) -> Self {
+        match src {
+            StylusHttpError::Http(e) => ProviderError::HTTPError(e),
+            e => ProviderError::JsonRpcClientError(Box::new(e)),
+        }
+    }
+}
+
+impl From<reqwest::Error> for StylusHttpError {
+    fn from(src: reqwest::Error) -> Self {
+        StylusHttpError::Http(src)
+    }
+}
+
+#[derive(Serialize)]
+struct Request<'a, T> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+/// The error reported once an endpoint keeps answering 429, in the shape providers
+/// that rate limit at the JSON-RPC level use
+fn rate_limited(retry_after: Option<Duration>) -> StylusHttpError {
+    StylusHttpError::JsonRpc(JsonRpcError {
+        code: 429,
+        message: "Too many requests (HTTP 429)".to_string(),
+        data: retry_after.map(|delay| serde_json::json!({ "retry_after": delay.as_secs_f64() })),
+    })
+}
+
+impl StylusHttp {
+    pub(crate) fn new(url: Url, client: reqwest::Client, retry: RateLimitRetry) -> Self {
+        StylusHttp { id: AtomicU64::new(1), client, url, retry }
+    }
+
+    /// The endpoint requests are sent to
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl JsonRpcClient for StylusHttp {
+    type Error = StylusHttpError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, StylusHttpError>
+    where
+        T: fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let id = self.id.fetch_add(1, Ordering::SeqCst);
+        let body = serde_json::to_vec(&Request { jsonrpc: "2.0", id, method, params })
+            .map_err(|err| StylusHttpError::Serde { err, text: format!("{} request", method) })?;
+
+        let mut retries = 0;
+        let response = loop {
+            let response = self
+                .client
+                .post(self.url.clone())
+                .header(CONTENT_TYPE, "application/json")
+                .body(body.clone())
+                .send()
+                .await?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                break response;
+            }
+            let retry_after = retry_after_of(&response);
+            let wait = retry_after.unwrap_or(RATE_LIMIT_BACKOFF * 2u32.saturating_pow(retries));
+            if retries >= self.retry.max_retries || wait > self.retry.max_wait {
+                return Err(rate_limited(retry_after));
+            }
+            retries += 1;
+            println!("⏳ {} was rate limited, retrying in {:?} ({}/{})", method, wait, retries, self.retry.max_retries);
+            tokio::time::sleep(wait).await;
+        };
+
+        let status = response.status();
+        let text = response.text().await?;
+        let response: Response = serde_json::from_str(&text).map_err(|err| StylusHttpError::Serde {
+            err,
+            text: format!("HTTP {}: {}", status, text),
+        })?;
+        if let Some(error) = response.error {
+            return Err(StylusHttpError::JsonRpc(error));
+        }
+        // A `null` result, e.g. for an unknown receipt, deserializes into `None`
+        let result = response.result.unwrap_or(serde_json::Value::Null);
+        serde_json::from_value(result).map_err(|err| StylusHttpError::Serde { err, text })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::prelude::{Middleware, Provider};
+    use ethers::types::U64;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{atomic::AtomicUsize, Arc},
+    };
+
+    /// Serve `responses` in order, one per connection, then keep repeating the last one.
+    /// Returns the URL and the number of requests received so far.
+    fn serve(responses: Vec<String>) -> (Url, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let served = Arc::new(AtomicUsize::new(0));
+        let count = served.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                reader.read_exact(&mut vec![0; length]).unwrap();
+                let index = count.fetch_add(1, Ordering::SeqCst).min(responses.len() - 1);
+                stream.write_all(responses[index].as_bytes()).unwrap();
+            }
+        });
+        (url, served)
+    }
+
+    fn http(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            headers,
+            body.len(),
+            body
+        )
+    }
+
+    fn too_many_requests(retry_after: &str) -> String {
+        http("429 Too Many Requests", &format!("Retry-After: {}\r\n", retry_after), "")
+    }
+
+    fn transport(url: Url, max_retries: u32) -> StylusHttp {
+        let retry = RateLimitRetry { max_retries, max_wait: Duration::from_secs(5) };
+        StylusHttp::new(url, reqwest::Client::new(), retry)
+    }
+
+    #[tokio::test]
+    async fn retries_after_retry_after_delay() {
+        let ok = http("200 OK", "", r#"{"jsonrpc":"2.0","id":1,"result":"0x10"}"#);
+        let (url, served) = serve(vec![too_many_requests("0"), ok]);
+        let provider = Provider::new(transport(url, 3));
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(16));
+        assert_eq!(served.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn long_retry_after_fails_right_away_with_the_delay() {
+        let (url, served) = serve(vec![too_many_requests("120")]);
+        let error = transport(url, 3).request::<_, U64>("eth_blockNumber", ()).await.unwrap_err();
+        let response = error.as_error_response().unwrap();
+        assert_eq!(response.code, 429);
+        assert_eq!(response.data, Some(serde_json::json!({ "retry_after": 120.0 })));
+        assert_eq!(served.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let (url, served) = serve(vec![too_many_requests("0")]);
+        let error = transport(url, 2).request::<_, U64>("eth_blockNumber", ()).await.unwrap_err();
+        assert_eq!(error.as_error_response().map(|response| response.code), Some(429));
+        assert_eq!(served.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn json_rpc_errors_pass_through() {
+        let body = r#"{"jsonrpc":"2.0","id":1,"error":{"code":3,"message":"execution reverted","data":"0x"}}"#;
+        let (url, _) = serve(vec![http("200 OK", "", body)]);
+        let error = transport(url, 3).request::<_, U64>("eth_call", ()).await.unwrap_err();
+        let response = error.as_error_response().unwrap();
+        assert_eq!((response.code, response.message.as_str()), (3, "execution reverted"));
+    }
+
+    #[tokio::test]
+    async fn null_result_is_none() {
+        let (url, _) = serve(vec![http("200 OK", "", r#"{"jsonrpc":"2.0","id":1,"result":null}"#)]);
+        let receipt: Option<U64> = transport(url, 3).request("eth_getTransactionReceipt", ()).await.unwrap();
+        assert_eq!(receipt, None);
+    }
+}
@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::StylusClient;
+
+/// What the RPC watchdog does when a call exceeds its latency threshold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlowRpcAction {
+    /// Print a warning
+    #[default]
+    Log,
+    /// Emit a `SlowRpc` event
+    Event,
+}
+
+/// Emitted when an RPC call takes longer than the watchdog threshold
+#[derive(Event, Debug, Clone)]
+pub struct SlowRpc {
+    pub method: String,
+    pub latency: Duration,
+}
+
+/// RPC latency metrics collected by the watchdog
+#[derive(Resource, Debug, Clone, Default)]
+pub struct StylusMetrics {
+    pub rpc_calls: u64,
+    pub slow_rpc_calls: u64,
+    pub last_latency: Option<Duration>,
+    pub max_latency: Duration,
+}
+
+/// The most recent RPC failures, newest last, for debug panels
+#[derive(Resource, Debug, Clone, Default)]
+pub struct StylusErrorLog {
+    pub recent: VecDeque<(Instant, String)>,
+}
+
+/// Failures kept in `StylusErrorLog` when `error_log_size` isn't set
+const DEFAULT_ERROR_LOG_SIZE: usize = 32;
+
+/// Measures RPC call latency and flags calls slower than a threshold.
+/// Latency tracking is disabled when no threshold is configured; failures are always collected.
+#[derive(Debug, Clone, Default)]
+pub struct RpcWatchdog {
+    pub threshold: Option<Duration>,
+    pub action: SlowRpcAction,
+    /// Failures kept in `StylusErrorLog`, 32 when unset
+    pub error_log_size: Option<usize>,
+    samples: Arc<Mutex<Vec<(String, Duration)>>>,
+    errors: Arc<Mutex<Vec<(Instant, String)>>>,
+}
+
+impl RpcWatchdog {
+    pub fn new(threshold: Option<Duration>, action: SlowRpcAction) -> Self {
+        Self {
+            threshold,
+            action,
+            ..Default::default()
+        }
+    }
+
+    /// Record a failed call for `StylusErrorLog`
+    pub fn record_error(&self, method: &str, error: &eyre::Report) {
+        if let Ok(mut errors) = self.errors.lock() {
+            errors.push((Instant::now(), format!("{}: {:#}", method, error)));
+        }
+    }
+
+    fn is_slow(&self, latency: Duration) -> bool {
+        self.threshold.is_some_and(|threshold| latency > threshold)
+    }
+
+    /// Record the latency of a finished call
+    pub fn record(&self, method: &str, latency: Duration) {
+        if self.threshold.is_none() {
+            return;
+        }
+        if self.is_slow(latency) && self.action == SlowRpcAction::Log {
+            println!("⚠️ Slow RPC: {} took {:?}", method, latency);
+        }
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.push((method.to_string(), latency));
+        }
+    }
+
+    fn drain(&self) -> Vec<(String, Duration)> {
+        self.samples
+            .lock()
+            .map(|mut samples| std::mem::take(&mut *samples))
+            .unwrap_or_default()
+    }
+
+    fn drain_errors(&self) -> Vec<(Instant, String)> {
+        self.errors
+            .lock()
+            .map(|mut errors| std::mem::take(&mut *errors))
+            .unwrap_or_default()
+    }
+}
+
+/// Move latency samples recorded by the client into `StylusMetrics` and `SlowRpc` events,
+/// and failures into `StylusErrorLog`
+pub fn drain_rpc_watchdog(
+    client: Option<Res<StylusClient>>,
+    mut metrics: ResMut<StylusMetrics>,
+    mut error_log: ResMut<StylusErrorLog>,
+    mut slow_events: EventWriter<SlowRpc>,
+) {
+    let Some(client) = client else {
+        return;
+    };
+
+    for error in client.rpc_watchdog.drain_errors() {
+        error_log.recent.push_back(error);
+    }
+    let capacity = client.rpc_watchdog.error_log_size.unwrap_or(DEFAULT_ERROR_LOG_SIZE);
+    while error_log.recent.len() > capacity {
+        error_log.recent.pop_front();
+    }
+
+    for (method, latency) in client.rpc_watchdog.drain() {
+        metrics.rpc_calls += 1;
+        metrics.last_latency = Some(latency);
+        metrics.max_latency = metrics.max_latency.max(latency);
+
+        if client.rpc_watchdog.is_slow(latency) {
+            metrics.slow_rpc_calls += 1;
+            if client.rpc_watchdog.action == SlowRpcAction::Event {
+                slow_events.send(SlowRpc { method, latency });
+            }
+        }
+    }
+}